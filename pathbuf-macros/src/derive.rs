@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Attribute, Data, DeriveInput, Error, Fields, LitStr, Result};
+
+/// Expands `#[derive(PathPart)]`.
+pub(crate) fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`PathPart` can only be derived for enums",
+        ));
+    };
+
+    let enum_ident = &input.ident;
+    let mut arms = Vec::new();
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "`PathPart` can only be derived for enums of unit variants",
+            ));
+        }
+
+        let ident = &variant.ident;
+        let name = match rename(&variant.attrs)? {
+            Some(name) => name,
+            None => snake_case(&ident.to_string()),
+        };
+
+        arms.push(quote!(#enum_ident::#ident => #name));
+    }
+
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(__P: ?Sized));
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(str: ::core::convert::AsRef<__P>));
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let body = quote! {
+        fn push_to(&self, path: &mut ::pathbuf::PartWriter<'_, __P>) {
+            path.push(match *self {
+                #(#arms,)*
+            });
+        }
+
+        fn len_hint(&self) -> usize {
+            let name: &str = match *self {
+                #(#arms,)*
+            };
+
+            name.len()
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::pathbuf::PathPart<__P> for #enum_ident #ty_generics #where_clause {
+            #body
+        }
+
+        impl #impl_generics ::pathbuf::PathPart<__P> for &#enum_ident #ty_generics #where_clause {
+            #body
+        }
+    })
+}
+
+/// Converts a variant name to snake_case, keeping acronyms together: `HttpServer` and `HTTPServer` are both
+/// `http_server`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                snake.push('_');
+            }
+        }
+
+        snake.extend(c.to_lowercase());
+    }
+
+    snake
+}
+
+/// Reads the name from a `#[path_part(rename = "...")]` attribute, if there is one.
+fn rename(attrs: &[Attribute]) -> Result<Option<String>> {
+    let mut name = None;
+
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("path_part"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("rename") {
+                return Err(meta.error("expected `rename = \"...\"`"));
+            }
+
+            let lit: LitStr = meta.value()?.parse()?;
+            let value = lit.value();
+
+            if value.is_empty() || value.contains(['/', '\\']) || value == "." || value == ".." {
+                return Err(Error::new_spanned(
+                    lit,
+                    "a renamed variant must be a single, non-empty path component",
+                ));
+            }
+
+            name = Some(value);
+            Ok(())
+        })?;
+    }
+
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::snake_case;
+
+    #[test]
+    fn snake_case_names() {
+        assert_eq!(snake_case("Logs"), "logs");
+        assert_eq!(snake_case("HttpServer"), "http_server");
+        assert_eq!(snake_case("HTTPServer"), "http_server");
+        assert_eq!(snake_case("Utf8Data"), "utf8_data");
+        assert_eq!(snake_case("IO"), "io");
+    }
+}
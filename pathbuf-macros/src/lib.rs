@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Procedural macros of the `pathbuf` crate.
+//!
+//! Use them through the `derive` and `proc-macro` features of `pathbuf` rather than depending on this crate directly.
+
+mod derive;
+mod pathbuf;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, Error};
+
+/// Derives `PathPart` for an enum of unit variants, pushing each variant as its snake_case name.
+///
+/// A variant can be given another name with `#[path_part(rename = "...")]`.
+#[proc_macro_derive(PathPart, attributes(path_part))]
+pub fn derive_path_part(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    derive::expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The implementation of `pathbuf!` with the `proc-macro` feature, which is given the path of the `pathbuf` crate
+/// followed by a `;` and the arguments.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __pathbuf(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as pathbuf::Input).expand().into()
+}
@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{Error, Expr, Ident, Lit, LitStr, Result, Token};
+
+/// The arguments of `pathbuf!`, after the path of the `pathbuf` crate.
+pub(crate) struct Input {
+    krate: TokenStream,
+    args: Vec<Arg>,
+    extension: Option<Expr>,
+}
+
+/// A single argument, in one of the forms `pathbuf!` accepts.
+enum Arg {
+    /// `"{id}.log"`
+    Template(LitStr),
+    /// `dir`
+    Plain(Expr),
+    /// `..segments`
+    Spread(Expr),
+    /// `?sub`
+    Optional(Expr),
+    /// `if release => "release"`, with an optional `, else "debug"`
+    If {
+        cond: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Option<Box<Expr>>,
+    },
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut krate = TokenStream::new();
+
+        while !input.peek(Token![;]) {
+            krate.extend([input.parse::<TokenTree>()?]);
+        }
+
+        input.parse::<Token![;]>()?;
+
+        let mut args = Vec::new();
+
+        while !input.is_empty() && !input.peek(Token![;]) {
+            args.push(input.parse()?);
+
+            if input.is_empty() || input.peek(Token![;]) {
+                break;
+            }
+
+            if !input.peek(Token![,]) {
+                return Err(input
+                    .error("expected `,` before the next component or `;` before the extension"));
+            }
+
+            input.parse::<Token![,]>()?;
+        }
+
+        let mut extension = None;
+
+        if input.parse::<Option<Token![;]>>()?.is_some() {
+            let ident: Ident = input
+                .parse()
+                .map_err(|error| Error::new(error.span(), "expected `ext = ...`"))?;
+
+            if ident != "ext" {
+                return Err(Error::new(ident.span(), "expected `ext = ...`"));
+            }
+
+            input.parse::<Token![=]>()?;
+            extension = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+
+            if !input.is_empty() {
+                return Err(input.error("unexpected tokens after the extension"));
+            }
+        }
+
+        Ok(Input {
+            krate,
+            args,
+            extension,
+        })
+    }
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.parse::<Option<Token![..]>>()?.is_some() {
+            return Ok(Arg::Spread(input.parse()?));
+        }
+
+        if input.parse::<Option<Token![?]>>()?.is_some() {
+            return Ok(Arg::Optional(input.parse()?));
+        }
+
+        if input.peek(Token![if]) {
+            let fork = input.fork();
+            fork.parse::<Token![if]>()?;
+
+            // An `if` expression without a `=>` is a plain component.
+            if fork.call(Expr::parse_without_eager_brace).is_ok() && fork.peek(Token![=>]) {
+                input.parse::<Token![if]>()?;
+                let cond = Box::new(input.call(Expr::parse_without_eager_brace)?);
+                input.parse::<Token![=>]>()?;
+                let then = input.parse()?;
+
+                let otherwise = if input.peek(Token![,]) && input.peek2(Token![else]) {
+                    input.parse::<Token![,]>()?;
+                    input.parse::<Token![else]>()?;
+                    Some(input.parse()?)
+                } else {
+                    None
+                };
+
+                return Ok(Arg::If {
+                    cond,
+                    then,
+                    otherwise,
+                });
+            }
+        }
+
+        // Like with `macro_rules!`, a literal on its own is a template, which has to be a string.
+        let fork = input.fork();
+        let negative = fork.parse::<Option<Token![-]>>()?.is_some();
+
+        if fork.peek(Lit) {
+            let literal: Lit = fork.parse()?;
+
+            if fork.is_empty() || fork.peek(Token![,]) || fork.peek(Token![;]) {
+                return match literal {
+                    Lit::Str(_) if !negative => Ok(Arg::Template(input.parse()?)),
+                    _ => Err(Error::new(
+                        literal.span(),
+                        "a literal component has to be a string, wrap other literals in parentheses, like `(42)`",
+                    )),
+                };
+            }
+        }
+
+        Ok(Arg::Plain(input.parse()?))
+    }
+}
+
+impl Arg {
+    fn span(&self) -> Span {
+        match self {
+            Arg::Template(template) => template.span(),
+            Arg::Plain(expr) | Arg::Spread(expr) | Arg::Optional(expr) => expr.span(),
+            Arg::If { cond, .. } => cond.span(),
+        }
+    }
+}
+
+impl Input {
+    pub(crate) fn expand(&self) -> TokenStream {
+        let krate = &self.krate;
+        let builder = quote!(::std::path::PathBuf);
+        let mut bindings = Vec::new();
+        let mut lens = Vec::new();
+        let mut pushes = Vec::new();
+        let mut warnings = Vec::new();
+        let temp = Ident::new("temp", Span::mixed_site());
+
+        for (index, arg) in self.args.iter().enumerate() {
+            let span = arg.span();
+            // The binding carries the span of the argument, which is where errors about its type point. The prefix
+            // keeps it apart from the names in the arguments.
+            let ident = format_ident!("__pathbuf_part{}", index, span = span);
+
+            let part = match arg {
+                Arg::Template(template) => {
+                    warnings.extend(lint_literal(template, index == 0));
+
+                    let push = Ident::new("push", Span::mixed_site());
+
+                    quote_spanned! {span=>
+                        #krate::__private::Format::new(#template, |#push: &mut dyn FnMut(::std::fmt::Arguments<'_>)| {
+                            #push(::std::format_args!(#template))
+                        })
+                    }
+                }
+                Arg::Plain(expr) => {
+                    let expr = foreign(krate, expr);
+                    quote_spanned!(span=> #krate::__private::Plain(#expr))
+                }
+                Arg::Spread(expr) => {
+                    let expr = foreign(krate, expr);
+                    quote_spanned!(span=> #krate::__private::Spread::new(#expr))
+                }
+                Arg::Optional(expr) => {
+                    let expr = foreign(krate, expr);
+                    quote_spanned!(span=> #krate::__private::Optional(#expr))
+                }
+                Arg::If {
+                    cond,
+                    then,
+                    otherwise: None,
+                } => {
+                    let then = foreign(krate, then);
+                    quote_spanned! {span=>
+                        #krate::__private::Optional(if #cond { ::std::option::Option::Some(#then) } else { ::std::option::Option::None })
+                    }
+                }
+                Arg::If {
+                    cond,
+                    then,
+                    otherwise: Some(otherwise),
+                } => {
+                    let then = foreign(krate, then);
+                    let otherwise = foreign(krate, otherwise);
+                    quote_spanned! {span=>
+                        if #cond {
+                            #krate::__private::Either::Left(#then)
+                        } else {
+                            #krate::__private::Either::Right(#otherwise)
+                        }
+                    }
+                }
+            };
+
+            bindings.push(quote!(let #ident = #part;));
+            lens.push(
+                quote_spanned!(span=> .saturating_add(#krate::__private::Part::<#builder>::joined_len(&#ident))),
+            );
+            pushes.push(quote_spanned!(span=> #krate::__private::Part::<#builder>::push_into(#ident, &mut #temp);));
+        }
+
+        if let Some(extension) = &self.extension {
+            let span = extension.span();
+            let ident = Ident::new("__pathbuf_extension", span);
+
+            bindings.push(
+                quote_spanned!(span=> let #ident = #krate::__private::Extension(#extension);),
+            );
+            lens.push(
+                quote_spanned!(span=> .saturating_add(#krate::__private::Part::<#builder>::joined_len(&#ident))),
+            );
+            pushes.push(quote_spanned!(span=> #krate::__private::Part::<#builder>::push_into(#ident, &mut #temp);));
+        }
+
+        quote! {{
+            #(#warnings)*
+            #(#bindings)*
+
+            let mut #temp: #builder = <#builder>::new();
+            #krate::__private::Builder::reserve(&mut #temp, 0_usize #(#lens)*);
+            #(#pushes)*
+
+            #temp
+        }}
+    }
+}
+
+/// Wraps an argument so that foreign types like integers can be pushed, like the `@foreign` rule of `macro_rules!`.
+fn foreign(krate: &TokenStream, expr: &Expr) -> TokenStream {
+    let value = Ident::new("value", Span::mixed_site());
+
+    quote_spanned! {expr.span()=> {
+        #[allow(unused_imports)]
+        use #krate::__private::{ForeignKind as _, PartKind as _};
+        #[allow(unused_parens)]
+        let #value = #expr;
+        (&#value).__pathbuf_kind().wrap(#value)
+    }}
+}
+
+/// Warns about a literal which replaces the path before it, climbs out of it or contains a separator. With the
+/// `strict` feature, this is an error instead.
+///
+/// A first argument is the start of the path, so it is only linted for separators, and not at all if it is rooted,
+/// like `/var/log` or `C:\\Users`, as such a path is specific to a platform anyway.
+///
+/// There are no custom warnings on stable Rust, so this uses a deprecated item which is named in the note.
+fn lint_literal(template: &LitStr, first: bool) -> Option<TokenStream> {
+    let value = template.value();
+
+    let (name, note) = if !first && value.starts_with(['/', '\\']) {
+        (
+            "absolute_component",
+            "this component starts with a separator, so it replaces the path before it",
+        )
+    } else if !first && value.split(['/', '\\']).any(|segment| segment == "..") {
+        (
+            "parent_dir_component",
+            "this component contains a `..` segment, so it climbs out of the path before it",
+        )
+    } else if value.contains(['/', '\\']) && !(first && is_rooted(&value)) {
+        (
+            "separator_in_component",
+            "this component contains a hard-coded separator, which is not portable; push its parts as separate \
+             components, or with `split(...)`",
+        )
+    } else {
+        return None;
+    };
+
+    if cfg!(feature = "strict") {
+        return Some(Error::new(template.span(), note).into_compile_error());
+    }
+
+    let ident = Ident::new(name, template.span());
+
+    Some(quote! {
+        {
+            #[deprecated(note = #note)]
+            #[allow(non_camel_case_types)]
+            struct #ident;
+
+            let _ = #ident;
+        }
+    })
+}
+
+/// Checks whether a literal starts with a root or a drive, like `/var/log` or `C:\\Users`.
+fn is_rooted(value: &str) -> bool {
+    let bytes = value.as_bytes();
+
+    value.starts_with(['/', '\\'])
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::Builder;
+use crate::confined::Confined;
+use std::ffi::CString;
+use std::fs::File;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::path::{Component, Path, PathBuf};
+
+/// A directory which paths are resolved beneath, even in the presence of symbolic links.
+///
+/// On Linux the path is opened with `openat2(2)` using `RESOLVE_BENEATH | RESOLVE_NO_MAGICLINKS`, so symbolic links
+/// are followed only as long as they stay beneath the root. On other systems, or when `openat2(2)` is unavailable,
+/// every component is opened one by one with `O_NOFOLLOW`, which rejects any symbolic link.
+///
+/// Because the file is opened relative to the directory handle, swapping a component for a symbolic link between
+/// checking and opening the path has no effect.
+#[derive(Debug)]
+pub struct Root {
+    path: PathBuf,
+    dir: File,
+}
+
+impl Root {
+    /// Opens the directory at `path` as a root.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Root> {
+        let path = path.as_ref();
+        let c_path = c_string(path.as_os_str().as_bytes())?;
+        let dir = open_at(
+            None,
+            &c_path,
+            libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+        )?;
+
+        Ok(Root {
+            path: path.to_path_buf(),
+            dir: File::from(dir),
+        })
+    }
+
+    /// Uses an already opened directory as a root, with `path` being the path it was opened from.
+    pub fn from_dir<P: Into<PathBuf>>(path: P, dir: File) -> Root {
+        Root {
+            path: path.into(),
+            dir,
+        }
+    }
+
+    /// Returns the path this root was opened from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens `path` read-only beneath this root.
+    ///
+    /// `path` is first resolved lexically like in [`confined_pathbuf!`][crate::confined_pathbuf], so absolute paths
+    /// and `..` segments which climb above the root are rejected with [`io::ErrorKind::InvalidInput`]. On success the
+    /// root path joined with the path the file was found at, after following symbolic links beneath the root, is
+    /// returned together with the open file.
+    ///
+    /// On Linux, that path is read back from `/proc/self/fd`. Without `/proc`, the path is opened one component at a
+    /// time like on other systems, where no symbolic link is followed and the resolved path is the lexical one.
+    pub fn open_beneath<P: AsRef<Path>>(&self, path: P) -> io::Result<(PathBuf, File)> {
+        let relative = resolve_lexically(path.as_ref())?;
+
+        #[cfg(target_os = "linux")]
+        match openat2_beneath(self.dir.as_fd(), &relative) {
+            Err(error) if is_unsupported(&error) => {}
+            Err(error) => return Err(error),
+            Ok(file) => {
+                if let Some(resolved) = resolved_beneath(self.dir.as_fd(), file.as_fd()) {
+                    return Ok((self.path.join(resolved), file));
+                }
+            }
+        }
+
+        walk_beneath(self.dir.as_fd(), &relative).map(|file| (self.path.join(&relative), file))
+    }
+}
+
+fn resolve_lexically(path: &Path) -> io::Result<PathBuf> {
+    let mut confined = Confined::default();
+    confined.reserve(path.as_os_str().len());
+
+    confined.push_part(Path::new(""));
+    confined.next_arg();
+    confined.push_part(path);
+
+    confined
+        .finish()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+}
+
+#[cfg(target_os = "linux")]
+fn openat2_beneath(root: BorrowedFd<'_>, relative: &Path) -> io::Result<File> {
+    let c_path = c_string(dot_if_empty(relative))?;
+
+    // SAFETY: `open_how` is a plain C struct for which all zero bytes is a valid value.
+    let mut how: libc::open_how = unsafe { std::mem::zeroed() };
+    how.flags = (libc::O_RDONLY | libc::O_CLOEXEC) as u64;
+    how.resolve = libc::RESOLVE_BENEATH | libc::RESOLVE_NO_MAGICLINKS;
+
+    // SAFETY: `c_path` and `how` outlive the call and `size_of_val(&how)` is the size of `how`.
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_openat2,
+            root.as_raw_fd(),
+            c_path.as_ptr(),
+            &how as *const libc::open_how,
+            std::mem::size_of_val(&how),
+        )
+    };
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: the syscall succeeded, so `fd` is a newly opened file descriptor which nothing else owns.
+    Ok(File::from(unsafe {
+        OwnedFd::from_raw_fd(fd as libc::c_int)
+    }))
+}
+
+/// Returns the path `file` was opened at relative to `root`, as the kernel reports it after following links.
+#[cfg(target_os = "linux")]
+fn resolved_beneath(root: BorrowedFd<'_>, file: BorrowedFd<'_>) -> Option<PathBuf> {
+    let fd_path = |fd: BorrowedFd<'_>| crate::pathbuf!["/proc/self/fd", fd.as_raw_fd()];
+
+    let root = std::fs::read_link(fd_path(root)).ok()?;
+    let file = std::fs::read_link(fd_path(file)).ok()?;
+
+    file.strip_prefix(root).ok().map(Path::to_path_buf)
+}
+
+#[cfg(target_os = "linux")]
+fn is_unsupported(error: &io::Error) -> bool {
+    // `ENOSYS` on kernels before 5.6, `EPERM` when a seccomp filter blocks the syscall.
+    matches!(error.raw_os_error(), Some(libc::ENOSYS) | Some(libc::EPERM))
+}
+
+/// Opens `relative` beneath `root` one component at a time, refusing to follow any symbolic link.
+///
+/// `relative` must already be lexically resolved, so it only contains normal components.
+fn walk_beneath(root: BorrowedFd<'_>, relative: &Path) -> io::Result<File> {
+    let mut components = relative.components().peekable();
+    let mut current: Option<OwnedFd> = None;
+
+    while let Some(component) = components.next() {
+        let Component::Normal(name) = component else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path is not lexically resolved",
+            ));
+        };
+
+        let mut flags = libc::O_RDONLY | libc::O_NOFOLLOW | libc::O_CLOEXEC;
+        if components.peek().is_some() {
+            flags |= libc::O_DIRECTORY;
+        }
+
+        let dir = current.as_ref().map_or(root, |fd| fd.as_fd());
+        current = Some(open_at(Some(dir), &c_string(name.as_bytes())?, flags)?);
+    }
+
+    match current {
+        Some(fd) => Ok(File::from(fd)),
+        None => Ok(File::from(root.try_clone_to_owned()?)),
+    }
+}
+
+fn open_at(dir: Option<BorrowedFd<'_>>, path: &CString, flags: libc::c_int) -> io::Result<OwnedFd> {
+    let dir = dir.map_or(libc::AT_FDCWD, |fd| fd.as_raw_fd());
+
+    // SAFETY: `path` is a valid C string which outlives the call.
+    let fd = unsafe { libc::openat(dir, path.as_ptr(), flags) };
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: `openat` succeeded, so `fd` is a newly opened file descriptor which nothing else owns.
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+#[cfg(target_os = "linux")]
+fn dot_if_empty(path: &Path) -> &[u8] {
+    match path.as_os_str().as_bytes() {
+        [] => b".",
+        bytes => bytes,
+    }
+}
+
+fn c_string(bytes: &[u8]) -> io::Result<CString> {
+    CString::new(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{walk_beneath, Root};
+    use crate::open_beneath;
+    use std::fs;
+    use std::io::{ErrorKind, Read};
+    use std::os::unix::fs::symlink;
+    use std::os::unix::io::AsFd;
+    use std::path::Path;
+
+    fn fixture() -> (tempfile::TempDir, Root) {
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret"), "secret").unwrap();
+
+        let tmp = tempfile::tempdir().unwrap();
+        let root_path = tmp.path().join("root");
+        fs::create_dir_all(root_path.join("docs")).unwrap();
+        fs::write(root_path.join("docs/readme"), "readme").unwrap();
+        symlink(outside.path(), root_path.join("escape")).unwrap();
+        symlink("/", root_path.join("absolute")).unwrap();
+        symlink("docs/readme", root_path.join("inside")).unwrap();
+
+        let root = Root::open(&root_path).unwrap();
+        (tmp, root)
+    }
+
+    fn read(mut file: fs::File) -> String {
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn opens_paths_beneath_root() {
+        let (_tmp, root) = fixture();
+
+        let (path, file) = root.open_beneath("docs/./../docs/readme").unwrap();
+
+        assert_eq!(path, root.path().join("docs/readme"));
+        assert_eq!(read(file), "readme");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn returns_resolved_path() {
+        let (_tmp, root) = fixture();
+
+        let (path, file) = root.open_beneath("inside").unwrap();
+
+        assert_eq!(path, root.path().join("docs/readme"));
+        assert_eq!(read(file), "readme");
+    }
+
+    #[test]
+    fn macro_accepts_pathbuf_syntax() {
+        let (_tmp, root) = fixture();
+        fs::write(root.path().join("docs/notes.txt"), "notes").unwrap();
+        let (dirs, stem, draft) = (["docs"], "notes", false);
+
+        let (path, file) =
+            open_beneath!(root; ..dirs, ?None::<&str>, if draft => "drafts", "{stem}"; ext = "txt")
+                .unwrap();
+
+        assert_eq!(path, root.path().join("docs/notes.txt"));
+        assert_eq!(read(file), "notes");
+    }
+
+    #[test]
+    fn rejects_lexical_escapes() {
+        let (_tmp, root) = fixture();
+
+        for path in ["../root/docs/readme", "/etc/passwd"] {
+            let error = root.open_beneath(path).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn rejects_symlinks_leaving_root() {
+        let (_tmp, root) = fixture();
+
+        assert!(root.open_beneath("escape/secret").is_err());
+        assert!(root.open_beneath("absolute/etc/passwd").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn rejects_magic_links() {
+        // `root` and `fd/0` are magic links beneath `/proc/self`, which `RESOLVE_NO_MAGICLINKS` refuses with `ELOOP`.
+        let root = Root::open("/proc/self").unwrap();
+
+        for path in ["root/etc/passwd", "fd/0"] {
+            let error = root.open_beneath(path).unwrap_err();
+            assert_eq!(error.raw_os_error(), Some(libc::ELOOP), "{path}");
+        }
+    }
+
+    #[test]
+    fn fallback_rejects_every_symlink() {
+        let (_tmp, root) = fixture();
+        let dir = root.dir.as_fd();
+
+        assert_eq!(
+            read(walk_beneath(dir, Path::new("docs/readme")).unwrap()),
+            "readme"
+        );
+        assert!(walk_beneath(dir, Path::new("escape/secret")).is_err());
+        assert!(walk_beneath(dir, Path::new("inside")).is_err());
+    }
+}
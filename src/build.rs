@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::part::{PartWriter, PathPart};
+use std::ffi::OsStr;
+use std::fmt::{self, Write};
+use std::mem;
+use std::path::{Component, Path, PathBuf};
+
+/// A path being built by one of the macros, one argument at a time.
+pub trait Builder: Sized {
+    /// The borrowed path type every component is converted to.
+    type Part: ?Sized + PartFromStr;
+
+    /// Reserves the capacity for arguments with the given summed [`Part::joined_len`].
+    ///
+    /// The length is only an estimate, so a capacity which cannot be reserved is skipped instead of panicking.
+    fn reserve(&mut self, joined_len: usize);
+
+    /// Pushes a component of the current argument.
+    fn push_part(&mut self, part: &Self::Part);
+
+    /// Pushes a formatted component of the current argument.
+    ///
+    /// Defaults to formatting the component into a [`String`] first, unless it is a plain string, and pushing that.
+    fn push_fmt(&mut self, args: fmt::Arguments<'_>) {
+        match args.as_str() {
+            Some(part) => self.push_part(Self::Part::part_from_str(part)),
+            None => self.push_part(Self::Part::part_from_str(&args.to_string())),
+        }
+    }
+
+    /// Moves on to the next argument.
+    fn next_arg(&mut self) {}
+
+    /// The borrowed string type of an extension.
+    type Extension: ?Sized;
+
+    /// Returns the number of bytes `extension` adds to the path, not counting the dot before it.
+    fn extension_len(extension: &Self::Extension) -> usize;
+
+    /// Sets the extension of the path, following [`PathBuf::set_extension`].
+    fn set_extension(&mut self, extension: &Self::Extension);
+}
+
+/// A borrowed path type which a string can be viewed as, like [`Path`].
+pub trait PartFromStr {
+    fn part_from_str(part: &str) -> &Self;
+}
+
+impl PartFromStr for Path {
+    fn part_from_str(part: &str) -> &Path {
+        Path::new(part)
+    }
+}
+
+/// Returns the capacity to reserve for arguments with the given summed [`Part::joined_len`].
+pub(crate) fn additional(is_empty: bool, joined_len: usize) -> usize {
+    // The first component of an empty path is pushed without a separator.
+    match is_empty {
+        true => joined_len.saturating_sub(1),
+        false => joined_len,
+    }
+}
+
+/// Returns the path of a builder which keeps the first error instead of stopping at it.
+pub(crate) fn finish<E>(path: PathBuf, error: Option<E>) -> Result<PathBuf, E> {
+    match error {
+        Some(error) => Err(error),
+        None => Ok(path),
+    }
+}
+
+impl Builder for PathBuf {
+    type Part = Path;
+
+    fn reserve(&mut self, joined_len: usize) {
+        let _ = self.try_reserve_exact(additional(self.as_os_str().is_empty(), joined_len));
+    }
+
+    fn push_part(&mut self, part: &Path) {
+        self.push(part);
+    }
+
+    /// Formats the component straight into the buffer, after the separator [`PathBuf::push`] would insert.
+    fn push_fmt(&mut self, args: fmt::Arguments<'_>) {
+        if let Some(part) = args.as_str() {
+            self.push(part);
+            return;
+        }
+
+        self.push("");
+
+        let mut path = mem::take(self).into_os_string();
+        let start = path.len();
+        path.write_fmt(args)
+            .expect("a formatting trait implementation returned an error");
+
+        let part = std::str::from_utf8(&path.as_encoded_bytes()[start..])
+            .expect("a formatted component is valid UTF-8");
+
+        if !replaces_path(Path::new(part)) {
+            *self = PathBuf::from(path);
+            return;
+        }
+
+        // Like `PathBuf::push`, a rooted component only keeps the prefix of the path before it.
+        let part = part.to_owned();
+        *self = match Path::new(&path).components().next() {
+            Some(prefix @ Component::Prefix(_)) => PathBuf::from(prefix.as_os_str()),
+            _ => PathBuf::new(),
+        };
+        self.push(part);
+    }
+
+    type Extension = OsStr;
+
+    fn extension_len(extension: &OsStr) -> usize {
+        extension.len()
+    }
+
+    fn set_extension(&mut self, extension: &OsStr) {
+        PathBuf::set_extension(self, extension);
+    }
+}
+
+/// Returns whether [`PathBuf::push`] would replace at least a part of the path with `part`.
+fn replaces_path(part: &Path) -> bool {
+    part.has_root() || matches!(part.components().next(), Some(Component::Prefix(_)))
+}
+
+/// A bound macro argument which can be pushed onto a `B`.
+pub trait Part<B: Builder> {
+    /// Returns the number of bytes this argument adds, including one separator per component.
+    fn joined_len(&self) -> usize;
+
+    fn push_into(self, builder: &mut B);
+}
+
+/// A single component, like `dir`.
+pub struct Plain<T>(pub T);
+
+impl<B: Builder, T: PathPart<B::Part>> Part<B> for Plain<T> {
+    fn joined_len(&self) -> usize {
+        self.0.len_hint().saturating_add(1)
+    }
+
+    fn push_into(self, builder: &mut B) {
+        self.0.push_to(&mut PartWriter::new(builder));
+        builder.next_arg();
+    }
+}
+
+/// A string literal component, which is a template for [`format_args!`], like `"{id}.log"`.
+pub struct Format<F> {
+    template_len: usize,
+    format: F,
+}
+
+impl<F: FnOnce(&mut dyn FnMut(fmt::Arguments<'_>))> Format<F> {
+    /// Takes the template for an estimate of the length and a closure passing the formatted arguments on.
+    pub fn new(template: &str, format: F) -> Self {
+        Format {
+            template_len: template.len(),
+            format,
+        }
+    }
+}
+
+impl<B: Builder, F: FnOnce(&mut dyn FnMut(fmt::Arguments<'_>))> Part<B> for Format<F> {
+    fn joined_len(&self) -> usize {
+        self.template_len.saturating_add(1)
+    }
+
+    fn push_into(self, builder: &mut B) {
+        (self.format)(&mut |args| builder.push_fmt(args));
+        builder.next_arg();
+    }
+}
+
+/// A component which is only pushed if it is `Some`, like `?sub`.
+pub struct Optional<T>(pub Option<T>);
+
+impl<B: Builder, T: PathPart<B::Part>> Part<B> for Optional<T> {
+    fn joined_len(&self) -> usize {
+        self.0
+            .as_ref()
+            .map_or(0, |part| part.len_hint().saturating_add(1))
+    }
+
+    fn push_into(self, builder: &mut B) {
+        if let Some(part) = &self.0 {
+            part.push_to(&mut PartWriter::new(builder));
+        }
+
+        builder.next_arg();
+    }
+}
+
+/// One of two components chosen by a condition, like `if release => "release", else "debug"`.
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<B: Builder, L: PathPart<B::Part>, R: PathPart<B::Part>> Part<B> for Either<L, R> {
+    fn joined_len(&self) -> usize {
+        match self {
+            Either::Left(part) => part.len_hint().saturating_add(1),
+            Either::Right(part) => part.len_hint().saturating_add(1),
+        }
+    }
+
+    fn push_into(self, builder: &mut B) {
+        match &self {
+            Either::Left(part) => part.push_to(&mut PartWriter::new(builder)),
+            Either::Right(part) => part.push_to(&mut PartWriter::new(builder)),
+        }
+
+        builder.next_arg();
+    }
+}
+
+/// Every item of an iterator, like `..segments`.
+pub struct Spread<T>(Vec<T>);
+
+impl<T> Spread<T> {
+    /// Collects the items up front, so their lengths are known before the path is allocated.
+    pub fn new<I: IntoIterator<Item = T>>(items: I) -> Self {
+        Spread(items.into_iter().collect())
+    }
+}
+
+impl<B: Builder, T: PathPart<B::Part>> Part<B> for Spread<T> {
+    fn joined_len(&self) -> usize {
+        self.0.iter().fold(0, |len: usize, item| {
+            len.saturating_add(item.len_hint()).saturating_add(1)
+        })
+    }
+
+    fn push_into(self, builder: &mut B) {
+        let mut writer = PartWriter::new(builder);
+
+        for item in &self.0 {
+            item.push_to(&mut writer);
+        }
+
+        builder.next_arg();
+    }
+}
+
+/// The extension of the path, like `; ext = "json"`.
+pub struct Extension<T>(pub T);
+
+impl<B: Builder, T: AsRef<B::Extension>> Part<B> for Extension<T> {
+    fn joined_len(&self) -> usize {
+        B::extension_len(self.0.as_ref()).saturating_add(1)
+    }
+
+    fn push_into(self, builder: &mut B) {
+        builder.set_extension(self.0.as_ref());
+        builder.next_arg();
+    }
+}
+
+/// Borrows the buffer of [`push_path!`][crate::push_path], whether it is given as a `PathBuf` or a `&mut PathBuf`.
+pub trait PathBufMut {
+    fn path_buf_mut(&mut self) -> &mut PathBuf;
+}
+
+impl PathBufMut for PathBuf {
+    fn path_buf_mut(&mut self) -> &mut PathBuf {
+        self
+    }
+}
+
+/// Removes the last `count` components of `path`, returning whether there were enough of them.
+pub fn pop_components(path: &mut PathBuf, count: usize) -> bool {
+    (0..count).all(|_| path.pop())
+}
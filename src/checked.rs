@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::{self, Builder};
+use crate::policy::PathPolicy;
+use crate::PathBufError;
+use std::ffi::OsStr;
+use std::path::{is_separator, Component, Path, PathBuf};
+
+/// Rejects a component which would replace or climb out of the path before it.
+pub(crate) fn check_component(index: usize, part: &Path) -> Result<(), PathBufError> {
+    if part.is_absolute() {
+        return Err(PathBufError::Absolute { index });
+    }
+
+    for component in part.components() {
+        match component {
+            Component::Prefix(_) => return Err(PathBufError::Prefix { index }),
+            Component::RootDir => return Err(PathBufError::Root { index }),
+            Component::ParentDir => return Err(PathBufError::ParentDir { index }),
+            Component::CurDir | Component::Normal(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects an extension which [`PathBuf::set_extension`] would panic on.
+pub(crate) fn check_extension(index: usize, extension: &OsStr) -> Result<(), PathBufError> {
+    if extension.to_string_lossy().chars().any(is_separator) {
+        return Err(PathBufError::Extension { index });
+    }
+
+    Ok(())
+}
+
+/// Builds the path for [`try_pathbuf!`][crate::try_pathbuf], keeping the first error.
+///
+/// Only the first component of the first argument is trusted. Every other one is checked, even if the first argument
+/// pushes several, like a spread.
+#[derive(Debug, Default)]
+pub struct Checked {
+    path: PathBuf,
+    index: usize,
+    pushed: bool,
+    /// Whether a normal component was pushed after the trusted one, which the extension may be set on.
+    beneath: bool,
+    policy: Option<PathPolicy>,
+    error: Option<PathBufError>,
+}
+
+impl Builder for Checked {
+    type Part = Path;
+
+    fn reserve(&mut self, joined_len: usize) {
+        Builder::reserve(&mut self.path, joined_len);
+    }
+
+    fn push_part(&mut self, part: &Path) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let trusted = self.index == 0 && !self.pushed;
+        self.pushed = true;
+
+        if !trusted {
+            let checked = check_component(self.index, part).and_then(|()| match &self.policy {
+                Some(policy) => policy.check(self.index, part),
+                None => Ok(()),
+            });
+
+            if let Err(error) = checked {
+                self.error = Some(error);
+                return;
+            }
+
+            self.beneath |= part
+                .components()
+                .any(|component| matches!(component, Component::Normal(_)));
+        }
+
+        self.path.push(part);
+    }
+
+    fn next_arg(&mut self) {
+        self.index += 1;
+    }
+
+    type Extension = OsStr;
+
+    fn extension_len(extension: &OsStr) -> usize {
+        extension.len()
+    }
+
+    fn set_extension(&mut self, extension: &OsStr) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Err(error) = check_extension(self.index, extension) {
+            self.error = Some(error);
+            return;
+        }
+
+        // Otherwise, the extension would rename the trusted component, like `/srv/uploads` to `/srv/uploads.json`.
+        if !self.beneath {
+            self.error = Some(PathBufError::NoFileName { index: self.index });
+            return;
+        }
+
+        self.path.set_extension(extension);
+
+        if let (Some(policy), Some(file_name)) = (&self.policy, self.path.file_name()) {
+            if let Err(error) = policy.check(self.index, Path::new(file_name)) {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+impl Checked {
+    pub fn with_policy(policy: PathPolicy) -> Self {
+        Checked {
+            policy: Some(policy),
+            ..Checked::default()
+        }
+    }
+
+    pub fn finish(self) -> Result<PathBuf, PathBufError> {
+        build::finish(self.path, self.error)
+    }
+}
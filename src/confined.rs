@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::{self, Builder};
+use crate::checked::check_extension;
+use crate::PathBufError;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+/// Builds the path for [`confined_pathbuf!`][crate::confined_pathbuf], keeping the first error.
+///
+/// The root is pushed unchanged, while the following arguments are resolved lexically: `.` is skipped and `..` pops
+/// a component which was pushed beneath the root.
+#[derive(Debug, Default)]
+pub struct Confined {
+    path: PathBuf,
+    depth: usize,
+    index: usize,
+    error: Option<PathBufError>,
+}
+
+impl Builder for Confined {
+    type Part = Path;
+
+    fn reserve(&mut self, joined_len: usize) {
+        Builder::reserve(&mut self.path, joined_len);
+    }
+
+    fn push_part(&mut self, part: &Path) {
+        let index = self.index;
+
+        if self.error.is_some() {
+            return;
+        }
+
+        if index == 0 {
+            self.path.push(part);
+            return;
+        }
+
+        if part.is_absolute() {
+            self.error = Some(PathBufError::Absolute { index });
+            return;
+        }
+
+        for component in part.components() {
+            match component {
+                Component::Prefix(_) => {
+                    self.error = Some(PathBufError::Prefix { index });
+                    return;
+                }
+                Component::RootDir => {
+                    self.error = Some(PathBufError::Root { index });
+                    return;
+                }
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if self.depth == 0 {
+                        self.error = Some(PathBufError::EscapesRoot { index });
+                        return;
+                    }
+
+                    self.path.pop();
+                    self.depth -= 1;
+                }
+                Component::Normal(name) => {
+                    self.path.push(name);
+                    self.depth += 1;
+                }
+            }
+        }
+    }
+
+    fn next_arg(&mut self) {
+        self.index += 1;
+    }
+
+    type Extension = OsStr;
+
+    fn extension_len(extension: &OsStr) -> usize {
+        extension.len()
+    }
+
+    fn set_extension(&mut self, extension: &OsStr) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Err(error) = check_extension(self.index, extension) {
+            self.error = Some(error);
+            return;
+        }
+
+        // Otherwise, the extension would rename the root, like `/srv/uploads` to `/srv/uploads.json`.
+        if self.depth == 0 {
+            self.error = Some(PathBufError::NoFileName { index: self.index });
+            return;
+        }
+
+        self.path.set_extension(extension);
+    }
+}
+
+impl Confined {
+    pub fn finish(self) -> Result<PathBuf, PathBufError> {
+        build::finish(self.path, self.error)
+    }
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kind::{ForeignKind, IntoPart};
+use crate::part::{PartWriter, PathPart};
+use chrono::{Datelike, NaiveDate};
+
+impl ForeignKind for NaiveDate {}
+
+impl ForeignKind for Option<NaiveDate> {}
+
+impl IntoPart for NaiveDate {
+    type Part = DatePart;
+
+    fn into_part(self) -> DatePart {
+        DatePart(self)
+    }
+}
+
+impl IntoPart for Option<NaiveDate> {
+    type Part = Option<DatePart>;
+
+    fn into_part(self) -> Option<DatePart> {
+        self.map(DatePart)
+    }
+}
+
+/// A [`NaiveDate`] pushed as the three components `YYYY/MM/DD`.
+pub struct DatePart(NaiveDate);
+
+impl<P: ?Sized> PathPart<P> for DatePart {
+    fn push_to(&self, path: &mut PartWriter<'_, P>) {
+        path.push_fmt(format_args!("{:04}", self.0.year()));
+        path.push_fmt(format_args!("{:02}", self.0.month()));
+        path.push_fmt(format_args!("{:02}", self.0.day()));
+    }
+
+    fn len_hint(&self) -> usize {
+        "YYYY/MM/DD".len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pathbuf;
+    use chrono::NaiveDate;
+    use std::path::PathBuf;
+
+    #[test]
+    fn year_month_day() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        let p = pathbuf!["logs", date, "app.log"];
+
+        assert_eq!(p, PathBuf::from("logs/2024/03/09/app.log"));
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn dates_in_every_form() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 31);
+        let archived = false;
+
+        let p = pathbuf![?date, if archived => "archive", else date.unwrap()];
+        assert_eq!(p, PathBuf::from("2024/01/31/2024/01/31"));
+        assert_eq!(pathbuf!["logs", ?None::<NaiveDate>], PathBuf::from("logs"));
+    }
+
+    #[cfg(feature = "camino")]
+    #[test]
+    fn utf8_date() {
+        use crate::utf8_pathbuf;
+        use camino::Utf8PathBuf;
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        assert_eq!(
+            utf8_pathbuf!["logs", date],
+            Utf8PathBuf::from("logs/2024/03/09")
+        );
+    }
+}
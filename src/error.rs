@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// The error returned by [`try_pathbuf!`][crate::try_pathbuf] and [`confined_pathbuf!`][crate::confined_pathbuf]
+/// when an argument is rejected, either for path traversal or by a [`PathPolicy`][crate::PathPolicy].
+///
+/// Every variant carries the zero-based index of the offending macro argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PathBufError {
+    /// The argument is an absolute path and would replace everything before it.
+    Absolute { index: usize },
+    /// The argument has a root (like `\foo` on Windows) and would discard the relative path before it.
+    Root { index: usize },
+    /// The argument has a prefix (like `C:` on Windows) and would replace everything before it.
+    Prefix { index: usize },
+    /// The argument contains a `..` segment and could climb out of the path before it.
+    ParentDir { index: usize },
+    /// The argument contains more `..` segments than there are components beneath the root.
+    EscapesRoot { index: usize },
+    /// The extension contains a path separator.
+    Extension { index: usize },
+    /// The extension is set, but no component was pushed beneath the first argument, so it would rename that one.
+    NoFileName { index: usize },
+    /// The argument contains a NUL byte.
+    Nul { index: usize },
+    /// The argument contains a control character.
+    ControlChar { index: usize },
+    /// The argument contains a character which Windows does not allow in a file name, like `?` or `:`.
+    ReservedChar { index: usize },
+    /// The argument has a component which is a device name of Windows, like `CON` or `COM1`.
+    ReservedName { index: usize },
+    /// The argument has a component ending with a `.` or a space.
+    TrailingDotOrSpace { index: usize },
+    /// The argument has a component which is longer than the policy allows.
+    TooLong { index: usize },
+}
+
+impl PathBufError {
+    /// Returns the zero-based index of the macro argument which was rejected.
+    pub fn index(&self) -> usize {
+        match *self {
+            PathBufError::Absolute { index }
+            | PathBufError::Root { index }
+            | PathBufError::Prefix { index }
+            | PathBufError::ParentDir { index }
+            | PathBufError::EscapesRoot { index }
+            | PathBufError::Extension { index }
+            | PathBufError::NoFileName { index }
+            | PathBufError::Nul { index }
+            | PathBufError::ControlChar { index }
+            | PathBufError::ReservedChar { index }
+            | PathBufError::ReservedName { index }
+            | PathBufError::TrailingDotOrSpace { index }
+            | PathBufError::TooLong { index } => index,
+        }
+    }
+}
+
+impl Display for PathBufError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            PathBufError::Absolute { index } => write!(f, "argument {index} is an absolute path"),
+            PathBufError::Root { index } => write!(f, "argument {index} has a root"),
+            PathBufError::Prefix { index } => write!(f, "argument {index} has a prefix"),
+            PathBufError::ParentDir { index } => {
+                write!(f, "argument {index} contains a `..` segment")
+            }
+            PathBufError::EscapesRoot { index } => {
+                write!(f, "argument {index} climbs above the root")
+            }
+            PathBufError::Extension { index } => {
+                write!(f, "extension at argument {index} contains a separator")
+            }
+            PathBufError::NoFileName { index } => {
+                write!(
+                    f,
+                    "extension at argument {index} has no file name to be set on"
+                )
+            }
+            PathBufError::Nul { index } => write!(f, "argument {index} contains a NUL byte"),
+            PathBufError::ControlChar { index } => {
+                write!(f, "argument {index} contains a control character")
+            }
+            PathBufError::ReservedChar { index } => {
+                write!(f, "argument {index} contains a reserved character")
+            }
+            PathBufError::ReservedName { index } => {
+                write!(f, "argument {index} is a reserved name")
+            }
+            PathBufError::TrailingDotOrSpace { index } => {
+                write!(f, "argument {index} ends with a dot or a space")
+            }
+            PathBufError::TooLong { index } => {
+                write!(f, "argument {index} has a component which is too long")
+            }
+        }
+    }
+}
+
+impl Error for PathBufError {}
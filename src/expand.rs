@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::{self, Builder};
+use std::borrow::{Borrow, Cow};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Display, Formatter};
+use std::hash::{BuildHasher, Hash};
+use std::path::{is_separator, Path, PathBuf};
+
+/// The environment variables and home directory used by [`expand_pathbuf!`][crate::expand_pathbuf].
+///
+/// [`ProcessEnv`] reads them from the current process. Maps from names to values implement it as well, so expansion
+/// can be tested without touching the process environment:
+///
+/// ```
+/// # use pathbuf::expand_pathbuf;
+/// # use std::collections::HashMap;
+/// # use std::path::PathBuf;
+/// #
+/// let env = HashMap::from([("HOME", "/home/alice"), ("APP", "demo")]);
+///
+/// assert_eq!(expand_pathbuf![env = env; "~/cache", ("${APP}")], Ok(PathBuf::from("/home/alice/cache/demo")));
+/// ```
+pub trait Env {
+    /// Returns the value of the variable `name`, or `None` if it is not set.
+    fn var_os(&self, name: &str) -> Option<OsString>;
+
+    /// Returns the home directory which `~` expands to.
+    ///
+    /// Defaults to the `HOME` variable, or `USERPROFILE` on Windows. An empty value counts as not set.
+    fn home_dir(&self) -> Option<PathBuf> {
+        let name = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+
+        self.var_os(name)
+            .filter(|home| !home.is_empty())
+            .map(PathBuf::from)
+    }
+}
+
+/// The [`Env`] of the current process.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProcessEnv;
+
+impl Env for ProcessEnv {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        env::var_os(name)
+    }
+}
+
+impl<T: Env + ?Sized> Env for &T {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        (**self).var_os(name)
+    }
+
+    fn home_dir(&self) -> Option<PathBuf> {
+        (**self).home_dir()
+    }
+}
+
+impl<K, V, S> Env for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<OsStr>,
+    S: BuildHasher,
+{
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        self.get(name).map(|value| value.as_ref().to_owned())
+    }
+}
+
+impl<K: Borrow<str> + Ord, V: AsRef<OsStr>> Env for BTreeMap<K, V> {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        self.get(name).map(|value| value.as_ref().to_owned())
+    }
+}
+
+/// The error returned by [`expand_pathbuf!`][crate::expand_pathbuf] when an argument cannot be expanded.
+///
+/// Every variant carries the zero-based index of the offending macro argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExpandError {
+    /// The argument refers to a variable which is not set.
+    Missing { index: usize, name: String },
+    /// The argument starts with `~`, but there is no home directory.
+    NoHome { index: usize },
+    /// The argument contains a `${` without a valid name and a closing `}`.
+    InvalidReference { index: usize },
+}
+
+impl ExpandError {
+    /// Returns the zero-based index of the macro argument which could not be expanded.
+    pub fn index(&self) -> usize {
+        match *self {
+            ExpandError::Missing { index, .. }
+            | ExpandError::NoHome { index }
+            | ExpandError::InvalidReference { index } => index,
+        }
+    }
+}
+
+impl Display for ExpandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::Missing { index, name } => {
+                write!(f, "argument {index} refers to `{name}`, which is not set")
+            }
+            ExpandError::NoHome { index } => {
+                write!(
+                    f,
+                    "argument {index} starts with `~`, but there is no home directory"
+                )
+            }
+            ExpandError::InvalidReference { index } => {
+                write!(
+                    f,
+                    "argument {index} contains an invalid `${{...}}` reference"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ExpandError {}
+
+/// Expands `$VAR`, `${VAR}` and `$$` in `part`, and a leading `~` if `tilde` is set.
+///
+/// A `$` which is not followed by a name or a `{` is kept as is, and so is a part which is not valid UTF-8.
+pub(crate) fn expand<'a>(
+    index: usize,
+    part: &'a Path,
+    tilde: bool,
+    env: &(impl Env + ?Sized),
+) -> Result<Cow<'a, Path>, ExpandError> {
+    let Some(text) = part.to_str() else {
+        return Ok(Cow::Borrowed(part));
+    };
+
+    let home = tilde
+        && text
+            .strip_prefix('~')
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(is_separator));
+
+    if !home && !text.contains('$') {
+        return Ok(Cow::Borrowed(part));
+    }
+
+    let mut expanded = OsString::with_capacity(text.len());
+    let mut rest = text;
+
+    if home {
+        let home = env.home_dir().ok_or(ExpandError::NoHome { index })?;
+        expanded.push(home);
+        rest = &rest[1..];
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        let (name, after) = if rest.starts_with('$') {
+            expanded.push("$");
+            rest = &rest[1..];
+            continue;
+        } else if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .filter(|&end| is_name(&braced[..end]))
+                .ok_or(ExpandError::InvalidReference { index })?;
+
+            (&braced[..end], &braced[end + 1..])
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+
+            if !is_name(&rest[..end]) {
+                expanded.push("$");
+                continue;
+            }
+
+            (&rest[..end], &rest[end..])
+        };
+
+        let value = env.var_os(name).ok_or_else(|| ExpandError::Missing {
+            index,
+            name: name.to_owned(),
+        })?;
+
+        expanded.push(value);
+        rest = after;
+    }
+
+    expanded.push(rest);
+
+    Ok(Cow::Owned(PathBuf::from(expanded)))
+}
+
+/// Checks that `name` is a valid variable name, like `XDG_DATA_HOME`.
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Builds the path for [`expand_pathbuf!`][crate::expand_pathbuf], keeping the first error.
+pub struct Expanded<'e> {
+    path: PathBuf,
+    env: &'e dyn Env,
+    index: usize,
+    pushed: bool,
+    error: Option<ExpandError>,
+}
+
+impl<'e> Expanded<'e> {
+    pub fn new(env: &'e dyn Env) -> Self {
+        Expanded {
+            path: PathBuf::new(),
+            env,
+            index: 0,
+            pushed: false,
+            error: None,
+        }
+    }
+
+    pub fn finish(self) -> Result<PathBuf, ExpandError> {
+        build::finish(self.path, self.error)
+    }
+}
+
+impl Builder for Expanded<'_> {
+    type Part = Path;
+
+    fn reserve(&mut self, joined_len: usize) {
+        Builder::reserve(&mut self.path, joined_len);
+    }
+
+    fn push_part(&mut self, part: &Path) {
+        if self.error.is_some() {
+            return;
+        }
+
+        // Only the first component may start with `~`, not further components of the first argument.
+        let leading = self.index == 0 && !self.pushed;
+        self.pushed = true;
+
+        match expand(self.index, part, leading, self.env) {
+            Ok(part) => self.path.push(part),
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn next_arg(&mut self) {
+        self.index += 1;
+    }
+
+    type Extension = OsStr;
+
+    fn extension_len(extension: &OsStr) -> usize {
+        extension.len()
+    }
+
+    fn set_extension(&mut self, extension: &OsStr) {
+        if self.error.is_none() {
+            self.path.set_extension(extension);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpandError;
+    use crate::expand_pathbuf;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn env() -> HashMap<&'static str, &'static str> {
+        HashMap::from([
+            ("HOME", "/home/alice"),
+            ("XDG_DATA_HOME", "/data"),
+            ("APP", "demo"),
+        ])
+    }
+
+    #[test]
+    fn variables() {
+        let env = env();
+        let p = expand_pathbuf![env = env; "$XDG_DATA_HOME/app", ("${APP}.d"), "logs"];
+        assert_eq!(p, Ok(PathBuf::from("/data/app/demo.d/logs")));
+    }
+
+    #[test]
+    fn tilde() {
+        let env = env();
+        assert_eq!(
+            expand_pathbuf![env = env; "~"],
+            Ok(PathBuf::from("/home/alice"))
+        );
+        assert_eq!(
+            expand_pathbuf![env = env; "~/cache", "~"],
+            Ok(PathBuf::from("/home/alice/cache/~"))
+        );
+        assert_eq!(
+            expand_pathbuf![env = env; ..["~", "~"]],
+            Ok(PathBuf::from("/home/alice/~"))
+        );
+        assert_eq!(
+            expand_pathbuf![env = env; "~bob"],
+            Ok(PathBuf::from("~bob"))
+        );
+    }
+
+    #[test]
+    fn inline_env() {
+        assert_eq!(
+            expand_pathbuf![env = HashMap::from([("HOME", "/h"), ("APP", "demo")]); "~", "$APP"],
+            Ok(PathBuf::from("/h/demo"))
+        );
+    }
+
+    #[test]
+    fn no_home() {
+        let env: HashMap<&str, &str> = HashMap::new();
+        assert_eq!(
+            expand_pathbuf![env = env; "~/cache"],
+            Err(ExpandError::NoHome { index: 0 })
+        );
+    }
+
+    #[test]
+    fn missing_variable() {
+        let env = env();
+        let dir = "$UNSET_DIR";
+
+        assert_eq!(
+            expand_pathbuf![env = env; "data", dir, "$APP"],
+            Err(ExpandError::Missing {
+                index: 1,
+                name: String::from("UNSET_DIR")
+            })
+        );
+    }
+
+    #[test]
+    fn literal_dollars() {
+        let env = env();
+        let p = expand_pathbuf![env = env; "cost$$", "$5", "a$", "$APP$APP"];
+        assert_eq!(p, Ok(PathBuf::from("cost$/$5/a$/demodemo")));
+    }
+
+    #[test]
+    fn invalid_reference() {
+        let env = env();
+        assert_eq!(
+            expand_pathbuf![env = env; "data", ("${APP")],
+            Err(ExpandError::InvalidReference { index: 1 })
+        );
+        assert_eq!(
+            expand_pathbuf![env = env; ("${1}")],
+            Err(ExpandError::InvalidReference { index: 0 })
+        );
+    }
+}
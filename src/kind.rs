@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+
+/// Picks how a plain argument is wrapped, with foreign types taking precedence over the fallback through auto-ref.
+///
+/// Foreign types like integers can't implement [`PathPart`][crate::PathPart] next to its blanket implementation, so
+/// they are wrapped in a type which does. `(&value).__pathbuf_kind()` finds `ForeignKind` for such a `value: T` or
+/// `value: Option<T>` without taking another reference, and only falls back to [`PartKind`] otherwise.
+pub trait ForeignKind {
+    fn __pathbuf_kind(&self) -> ForeignTag {
+        ForeignTag
+    }
+}
+
+pub struct ForeignTag;
+
+impl ForeignTag {
+    pub fn wrap<T: IntoPart>(self, value: T) -> T::Part {
+        value.into_part()
+    }
+}
+
+/// Wraps a foreign type found by [`ForeignKind`] in a part which can be pushed.
+pub trait IntoPart {
+    type Part;
+
+    fn into_part(self) -> Self::Part;
+}
+
+/// The fallback of [`ForeignKind`], which keeps the argument as it is.
+pub trait PartKind {
+    fn __pathbuf_kind(&self) -> PartTag {
+        PartTag
+    }
+}
+
+impl<T: ?Sized> PartKind for &T {}
+
+pub struct PartTag;
+
+impl PartTag {
+    pub fn wrap<T>(self, value: T) -> T {
+        value
+    }
+}
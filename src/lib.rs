@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
-//! `pathbuf` provides a single macro, [`pathbuf!`][pathbuf], which gives a [`vec!`][std_vec]-like syntax
+//! `pathbuf` provides the [`pathbuf!`][pathbuf] macro, which gives a [`vec!`][std_vec]-like syntax
 //! for constructing [`PathBuf`][std_path_pathbuf]s.
 //!
 //! # Example
@@ -18,10 +18,67 @@
 //! }
 //! ```
 //!
+//! With the `camino` feature, [`utf8_pathbuf!`][utf8_pathbuf] builds a [`camino::Utf8PathBuf`] with the same syntax.
+//!
+//! With the `typed-path` feature, [`unix_pathbuf!`][unix_pathbuf] and [`windows_pathbuf!`][windows_pathbuf] build
+//! paths with the rules of a specific platform, no matter which platform the code runs on.
+//!
+//! [`normalized_pathbuf!`][normalized_pathbuf] folds `.` and `..` segments while building the path, without
+//! touching the filesystem.
+//!
+//! [`absolute_pathbuf!`][absolute_pathbuf] and [`canonical_pathbuf!`][canonical_pathbuf] resolve the built path
+//! against the filesystem, with errors naming the first component which does not exist.
+//!
+//! [`expand_pathbuf!`][expand_pathbuf] expands a leading `~` and `$VAR` references, like paths from configuration
+//! files.
+//!
+//! [`config_pathbuf!`][config_pathbuf], [`cache_pathbuf!`][cache_pathbuf], [`data_pathbuf!`][data_pathbuf],
+//! [`state_pathbuf!`][state_pathbuf] and [`runtime_pathbuf!`][runtime_pathbuf] build paths beneath the XDG base
+//! directories.
+//!
+//! [`manifest_pathbuf!`][manifest_pathbuf] and [`workspace_pathbuf!`][workspace_pathbuf] build paths beneath the
+//! calling package or its workspace, like fixtures in tests.
+//!
+//! If every component is a string literal, [`path!`][path] joins them at compile time, so the path can be used in
+//! constants without allocating.
+//!
+//! A [`PathTemplate`] is a layout like `{root}/{tenant}/{id}.json`, which can come from configuration and is parsed
+//! once to be rendered many times.
+//!
+//! Components can be of any type implementing [`PathPart`], which covers everything implementing
+//! [`AsRef<Path>`][std_path_path] and can be implemented for domain types. With the `derive` feature,
+//! `#[derive(PathPart)]` maps the variants of an enum to snake_case component names. [`split`] pushes a
+//! `/`-separated string as separate components, so the path only has native separators on every platform. With the
+//! `chrono` feature, a `chrono::NaiveDate` is pushed as `YYYY/MM/DD`.
+//!
+//! # Extensions
+//!
+//! Instead of calling [`PathBuf::set_extension`][std_path_pathbuf_set_extension] afterwards, the extension can be
+//! given in a clause after the components, separated by a semicolon:
+//!
+//! ```
+//! # use pathbuf::pathbuf;
+//! # use std::path::PathBuf;
+//! #
+//! let name = "backup";
+//!
+//! assert_eq!(pathbuf!["dumps", name; ext = "tar.gz"], PathBuf::from("dumps/backup.tar.gz"));
+//! ```
+//!
+//! The clause follows [`PathBuf::set_extension`][std_path_pathbuf_set_extension], so it replaces an existing
+//! extension of the last component and removes it when given an empty string. An extension containing a path
+//! separator panics, except in [`try_pathbuf!`][try_pathbuf] and [`confined_pathbuf!`][confined_pathbuf], which
+//! return an error instead. They also return an error if no component is left beneath the first argument, as the
+//! extension would rename it, like `/srv/uploads` to `/srv/uploads.json`.
+//!
 //! # Security
 //!
 //! As the macro relies on [`std::path::PathBuf::push`] there is also no protection against path traversal attacks.
 //! Therefore no path element shall be untrusted user input without validation or sanitisation.
+//! [`try_pathbuf!`][try_pathbuf] performs that validation and returns an error instead, while
+//! [`confined_pathbuf!`][confined_pathbuf] resolves `.` and `..` and guarantees that the result stays under a root.
+//! Given a [`PathPolicy`], `try_pathbuf!` also rejects components which are not valid file names, like ones with a
+//! NUL byte or the reserved names of Windows.
 //!
 //! An example for a path traversal/override on an UNIX system:
 //!
@@ -36,9 +93,115 @@
 //! # }
 //! ```
 //!
+//! When untrusted input names a single file, like the title of an upload, wrap it in [`sanitize`]. Instead of
+//! rejecting the input, it replaces separators and everything else which is not allowed in a file name, so the
+//! input always becomes exactly one component beneath the path before it:
+//!
+//! ```
+//! # use pathbuf::{pathbuf, sanitize};
+//! # use std::path::PathBuf;
+//! #
+//! # #[cfg(unix)]
+//! # {
+//! let user_input = "/etc/shadow";
+//! assert_eq!(pathbuf!["/tmp", sanitize(user_input)], PathBuf::from("/tmp/_etc_shadow"));
+//! # }
+//! ```
+//!
+//! A lexical check cannot see symbolic links. With the `beneath` feature on Unix, [`open_beneath!`][open_beneath]
+//! opens the built path through a [`Root`] directory handle, so links which leave the root are refused as well.
+//!
 //! [pathbuf]: macro.pathbuf.html
+//! [normalized_pathbuf]: macro.normalized_pathbuf.html
+//! [path]: macro.path.html
+//! [try_pathbuf]: macro.try_pathbuf.html
+//! [unix_pathbuf]: macro.unix_pathbuf.html
+//! [utf8_pathbuf]: macro.utf8_pathbuf.html
+//! [windows_pathbuf]: macro.windows_pathbuf.html
+//! [absolute_pathbuf]: macro.absolute_pathbuf.html
+//! [canonical_pathbuf]: macro.canonical_pathbuf.html
+//! [expand_pathbuf]: macro.expand_pathbuf.html
+//! [config_pathbuf]: macro.config_pathbuf.html
+//! [cache_pathbuf]: macro.cache_pathbuf.html
+//! [data_pathbuf]: macro.data_pathbuf.html
+//! [state_pathbuf]: macro.state_pathbuf.html
+//! [runtime_pathbuf]: macro.runtime_pathbuf.html
+//! [manifest_pathbuf]: macro.manifest_pathbuf.html
+//! [workspace_pathbuf]: macro.workspace_pathbuf.html
+//! [confined_pathbuf]: macro.confined_pathbuf.html
+//! [open_beneath]: macro.open_beneath.html
 //! [std_vec]: https://doc.rust-lang.org/std/macro.vec.html "Documentation for std::vec (macro)"
+//! [std_path_path]: https://doc.rust-lang.org/std/path/struct.Path.html "Documentation for std::path::Path (struct)"
 //! [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+//! [std_path_pathbuf_set_extension]: https://doc.rust-lang.org/std/path/struct.PathBuf.html#method.set_extension "Documentation for std::path::PathBuf::set_extension (method)"
+
+// Lets the code generated by `#[derive(PathPart)]` refer to `::pathbuf` from within the crate's own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as pathbuf;
+
+#[cfg(all(unix, feature = "beneath"))]
+mod beneath;
+mod build;
+mod checked;
+mod confined;
+#[cfg(feature = "chrono")]
+mod date;
+mod error;
+mod expand;
+mod kind;
+mod manifest;
+mod normalized;
+mod number;
+mod part;
+mod policy;
+mod resolve;
+mod sanitize;
+mod split;
+mod static_path;
+mod template;
+#[cfg(feature = "typed-path")]
+mod typed;
+#[cfg(feature = "camino")]
+mod utf8;
+mod xdg;
+
+#[cfg(all(unix, feature = "beneath"))]
+pub use beneath::Root;
+pub use error::PathBufError;
+pub use expand::{Env, ExpandError, ProcessEnv};
+pub use number::{pad, Integer, Padded};
+pub use part::{PartWriter, PathPart};
+#[cfg(feature = "derive")]
+pub use pathbuf_macros::PathPart;
+pub use policy::PathPolicy;
+pub use resolve::ResolveError;
+pub use sanitize::{sanitize, Sanitized};
+pub use split::{split, Split};
+pub use static_path::StaticPath;
+pub use template::{ArgWriter, PathTemplate, TemplateArgs, TemplateError};
+pub use xdg::BaseDir;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::build::{
+        pop_components, Builder, Either, Extension, Format, Optional, Part, PathBufMut, Plain,
+        Spread,
+    };
+    pub use crate::checked::Checked;
+    pub use crate::confined::Confined;
+    pub use crate::expand::{Expanded, ProcessEnv};
+    pub use crate::kind::{ForeignKind, PartKind};
+    pub use crate::manifest::find_workspace_root;
+    pub use crate::normalized::Normalized;
+    pub use crate::resolve::{absolute, canonicalize};
+    pub use crate::static_path::check_literals;
+    #[cfg(feature = "camino")]
+    pub use camino::Utf8PathBuf;
+    #[cfg(feature = "proc-macro")]
+    pub use pathbuf_macros::__pathbuf;
+    #[cfg(feature = "typed-path")]
+    pub use typed_path::{UnixPathBuf, WindowsPathBuf};
+}
 
 /// Creates a [`PathBuf`][std_path_pathbuf] containing the arguments.
 ///
@@ -57,27 +220,715 @@
 /// }
 /// ```
 ///
+/// An argument prefixed with `..` is spread, pushing every item of an [`IntoIterator`] whose items implement
+/// [`AsRef<Path>`][std_path_path] in its place:
+///
+/// ```
+/// # use pathbuf::pathbuf;
+/// # use std::path::PathBuf;
+/// #
+/// let segments = vec!["blog", "2024", "hello-world"];
+///
+/// assert_eq!(
+///     pathbuf!["public", ..&segments, "index.html"],
+///     PathBuf::from("public/blog/2024/hello-world/index.html")
+/// );
+/// ```
+///
+/// An argument prefixed with `?` is an [`Option`] of a component, which is only pushed if it is `Some`:
+///
+/// ```
+/// # use pathbuf::pathbuf;
+/// # use std::path::PathBuf;
+/// #
+/// let locale: Option<&str> = None;
+///
+/// assert_eq!(pathbuf!["docs", ?locale, "index.html"], PathBuf::from("docs/index.html"));
+/// assert_eq!(pathbuf!["docs", ?Some("de"), "index.html"], PathBuf::from("docs/de/index.html"));
+/// ```
+///
+/// An argument of the form `if condition => component` is only pushed if the condition holds, and may be followed by
+/// `else component` to push another component otherwise:
+///
+/// ```
+/// # use pathbuf::pathbuf;
+/// # use std::path::PathBuf;
+/// #
+/// let release = true;
+/// let windows = false;
+///
+/// assert_eq!(
+///     pathbuf!["target", if release => "release", else "debug", if windows => "win", "app"],
+///     PathBuf::from("target/release/app")
+/// );
+/// ```
+///
+/// A string literal argument is a template for [`format_args!`][std_format_args], so identifiers can be captured
+/// inline like with [`format!`][std_format]. The component is written straight into the buffer, without allocating an
+/// intermediate [`String`]:
+///
+/// ```
+/// # use pathbuf::pathbuf;
+/// # use std::path::PathBuf;
+/// #
+/// let (id, rev) = (42, 7);
+///
+/// assert_eq!(pathbuf!["logs", "{id}-{rev}.log"], PathBuf::from("logs/42-7.log"));
+/// ```
+///
+/// Literal braces are escaped by doubling them, like `"{{draft}}"`. Alternatively, a literal wrapped in parentheses
+/// is taken as is, like `("{draft}")`. As the length of a formatted component is only known once it is written, the
+/// pre-allocation uses the length of the template.
+///
+/// Integer arguments are pushed as their decimal digits, written straight into the buffer as well. Negative values
+/// keep their `-`. [`pad`] adds leading zeros up to a width, following the rules of [`format!`][std_format]:
+///
+/// ```
+/// # use pathbuf::{pad, pathbuf};
+/// # use std::path::PathBuf;
+/// #
+/// let (shard, segment) = (42_u16, 17_u64);
+///
+/// assert_eq!(pathbuf!["data", pad(4, shard), segment], PathBuf::from("data/0042/17"));
+/// ```
+///
+/// An integer literal has to be wrapped in parentheses, like `(42)`, as a bare literal is taken as a template, which
+/// has to be a string:
+///
+/// ```compile_fail
+/// # use pathbuf::pathbuf;
+/// #
+/// let shard = pathbuf!["data", 42];
+/// ```
+///
+/// ```
+/// # use pathbuf::pathbuf;
+/// # use std::path::PathBuf;
+/// #
+/// assert_eq!(pathbuf!["data", (42)], PathBuf::from("data/42"));
+/// ```
+///
+/// After the components, the extension of the path can be set with a `; ext = extension` clause, as described in
+/// the [crate documentation][crate#extensions].
+///
+/// Every argument is evaluated exactly once. The [`PathBuf`][std_path_pathbuf] is pre-allocated with the byte length
+/// of all arguments plus their separators, so the path itself is allocated only once, unless a formatted component
+/// turns out longer than its template. The items of a spread are collected into a [`Vec`] first, so their lengths
+/// are known up front, which is one more allocation per non-empty spread.
+///
+/// With the `proc-macro` feature, this macro is implemented as a procedural macro with the same syntax. Its errors
+/// point at the offending argument instead of the expansion, and it warns about a string literal after the first
+/// argument which starts with a separator or contains a `..` segment, as that replaces or climbs out of the path
+/// before it.
+///
+/// It also warns about a string literal containing a `/` or `\`, like `"sub/file.txt"`, unless it is a rooted first
+/// argument like `"/var/log"`. Such a literal is pushed as a single component, so the path mixes separators on
+/// Windows. Push the parts as separate components instead, or split the literal with [`split`]:
+///
+/// ```
+/// # use pathbuf::{pathbuf, split};
+/// # use std::path::Path;
+/// #
+/// fn asset(dir: &Path) {
+///     let icon = pathbuf![dir, "img", "logo.png"];
+///     let font = pathbuf![dir, split("fonts/inter/regular.woff2")];
+/// #   let _ = (icon, font);
+/// }
+/// ```
+///
+/// The `strict` feature turns on the `proc-macro` feature and makes these warnings errors.
+///
+/// [std_format]: https://doc.rust-lang.org/std/macro.format.html "Documentation for std::format (macro)"
+/// [std_format_args]: https://doc.rust-lang.org/std/macro.format_args.html "Documentation for std::format_args (macro)"
+/// [std_path_path]: https://doc.rust-lang.org/std/path/struct.Path.html "Documentation for std::path::Path (struct)"
 /// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
 #[macro_export]
 macro_rules! pathbuf {
-    ( $( $part:expr ),* ) => {{
-        use std::path::PathBuf;
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf!($($args)*)
+    };
+}
+
+#[cfg(not(feature = "proc-macro"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf_build!(std::path::PathBuf = std::path::PathBuf::new(); []; $($args)*)
+    };
+}
+
+#[cfg(feature = "proc-macro")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__private::__pathbuf!($crate; $($args)*)
+    };
+}
 
-        let mut temp = PathBuf::with_capacity( $( std::mem::size_of_val($part) + )* 0);
+/// Binds every argument exactly once, then pre-allocates the builder from their byte lengths and pushes them.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __pathbuf_build {
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; ) => {{
+        let mut temp: $builder = $init;
+        $crate::__private::Builder::reserve(&mut temp, 0_usize $( .saturating_add($crate::__private::Part::<$builder>::joined_len(&$bound)) )*);
 
         $(
-            temp.push($part);
+            $crate::__private::Part::<$builder>::push_into($bound, &mut temp);
         )*
 
         temp
     }};
 
-    ($( $part:expr, )*) => ($crate::pathbuf![$($part),*])
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; ; ext = $ext:expr $(,)? ) => {{
+        let part = $crate::__private::Extension($ext);
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ];)
+    }};
+
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; if $( $rest:tt )* ) => {
+        $crate::__pathbuf_build!(@if $builder = $init; [ $( $bound )* ]; []; $( $rest )*)
+    };
+
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; .. $( $rest:tt )* ) => {
+        $crate::__pathbuf_build!(@bind $builder = $init; [ $( $bound )* ]; $crate::__private::Spread::new; $( $rest )*)
+    };
+
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; ? $( $rest:tt )* ) => {
+        $crate::__pathbuf_build!(@bind $builder = $init; [ $( $bound )* ]; $crate::__private::Optional; $( $rest )*)
+    };
+
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; $template:literal , $( $rest:tt )* ) => {{
+        let part = $crate::__pathbuf_build!(@format $template);
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( $rest )*)
+    }};
+
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; $template:literal $( ; $( $rest:tt )* )? ) => {{
+        let part = $crate::__pathbuf_build!(@format $template);
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( ; $( $rest )* )?)
+    }};
+
+    ( $builder:ty = $init:expr; [ $( $bound:ident )* ]; $( $rest:tt )* ) => {
+        $crate::__pathbuf_build!(@bind $builder = $init; [ $( $bound )* ]; $crate::__private::Plain; $( $rest )*)
+    };
+
+    ( @format $template:literal ) => {
+        $crate::__private::Format::new($template, |push: &mut dyn FnMut(std::fmt::Arguments<'_>)| {
+            push(format_args!($template))
+        })
+    };
+
+    // Foreign types like integers, and options of them, are wrapped in a part which can be pushed, everything else is
+    // passed on as is.
+    ( @foreign $part:expr ) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{ForeignKind as _, PartKind as _};
+        #[allow(unused_parens)]
+        let value = $part;
+        (&value).__pathbuf_kind().wrap(value)
+    }};
+
+    // Every component is followed by a `,` before the next one, a `;` before the clauses, or nothing.
+    ( @bind $builder:ty = $init:expr; [ $( $bound:ident )* ]; $wrap:path; $part:expr , $( $rest:tt )* ) => {{
+        let part = $wrap($crate::__pathbuf_build!(@foreign $part));
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( $rest )*)
+    }};
+
+    ( @bind $builder:ty = $init:expr; [ $( $bound:ident )* ]; $wrap:path; $part:expr $( ; $( $rest:tt )* )? ) => {{
+        let part = $wrap($crate::__pathbuf_build!(@foreign $part));
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( ; $( $rest )* )?)
+    }};
+
+    // The condition of `if cond => part` is collected token by token up to the `=>`, so that an `if` expression
+    // without one is still taken as a plain component.
+    ( @if $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; => $then:expr , else $( $rest:tt )* ) => {
+        $crate::__pathbuf_build!(@else $builder = $init; [ $( $bound )* ]; [ $( $cond )* ]; $then; $( $rest )*)
+    };
+
+    ( @if $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; => $then:expr , $( $rest:tt )* ) => {{
+        let part = $crate::__private::Optional(if $( $cond )* { Some($crate::__pathbuf_build!(@foreign $then)) } else { None });
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( $rest )*)
+    }};
+
+    ( @if $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; => $then:expr $( ; $( $rest:tt )* )? ) => {{
+        let part = $crate::__private::Optional(if $( $cond )* { Some($crate::__pathbuf_build!(@foreign $then)) } else { None });
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( ; $( $rest )* )?)
+    }};
+
+    ( @if $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; , $( $rest:tt )* ) => {
+        $crate::__pathbuf_build!(@bind $builder = $init; [ $( $bound )* ]; $crate::__private::Plain; if $( $cond )* , $( $rest )*)
+    };
+
+    ( @if $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; $( ; $( $rest:tt )* )? ) => {
+        $crate::__pathbuf_build!(@bind $builder = $init; [ $( $bound )* ]; $crate::__private::Plain; if $( $cond )* $( ; $( $rest )* )?)
+    };
+
+    ( @if $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; $next:tt $( $rest:tt )* ) => {
+        $crate::__pathbuf_build!(@if $builder = $init; [ $( $bound )* ]; [ $( $cond )* $next ]; $( $rest )*)
+    };
+
+    ( @else $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; $then:expr; $else:expr , $( $rest:tt )* ) => {{
+        let part = $crate::__pathbuf_build!(@either [ $( $cond )* ]; $then; $else);
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( $rest )*)
+    }};
+
+    ( @else $builder:ty = $init:expr; [ $( $bound:ident )* ]; [ $( $cond:tt )* ]; $then:expr; $else:expr $( ; $( $rest:tt )* )? ) => {{
+        let part = $crate::__pathbuf_build!(@either [ $( $cond )* ]; $then; $else);
+        $crate::__pathbuf_build!($builder = $init; [ $( $bound )* part ]; $( ; $( $rest )* )?)
+    }};
+
+    ( @either [ $( $cond:tt )* ]; $then:expr; $else:expr ) => {
+        if $( $cond )* {
+            $crate::__private::Either::Left($crate::__pathbuf_build!(@foreign $then))
+        } else {
+            $crate::__private::Either::Right($crate::__pathbuf_build!(@foreign $else))
+        }
+    };
+}
+
+/// Creates a [`Utf8PathBuf`][camino::Utf8PathBuf] containing the arguments.
+///
+/// `utf8_pathbuf!` accepts the same syntax as [`pathbuf!`][pathbuf], but every component has to implement
+/// [`AsRef<Utf8Path>`][camino::Utf8Path], like `&str`, [`String`] or [`Utf8PathBuf`][camino::Utf8PathBuf]. Passing a
+/// component which may not be UTF-8, like a [`Path`][std_path_path], is a type error rather than a runtime failure.
+///
+/// ```
+/// # use pathbuf::utf8_pathbuf;
+/// # use camino::{Utf8Path, Utf8PathBuf};
+/// #
+/// let dir = Utf8Path::new("logs");
+/// let id = 42;
+///
+/// assert_eq!(utf8_pathbuf![dir, "{id}"; ext = "log"], Utf8PathBuf::from("logs/42.log"));
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_path]: https://doc.rust-lang.org/std/path/struct.Path.html "Documentation for std::path::Path (struct)"
+#[cfg(feature = "camino")]
+#[macro_export]
+macro_rules! utf8_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf_build!(
+            $crate::__private::Utf8PathBuf = $crate::__private::Utf8PathBuf::new(); []; $($args)*
+        )
+    };
+}
+
+/// Creates a [`UnixPathBuf`][typed_path::UnixPathBuf] containing the arguments, on any platform.
+///
+/// `unix_pathbuf!` accepts the same syntax as [`pathbuf!`][pathbuf], but joins the components with `/` and only
+/// treats components starting with `/` as absolute, like on Unix. Components can be anything implementing
+/// [`AsRef<UnixPath>`][typed_path::UnixPath], like `&str`, `&[u8]` or another [`UnixPathBuf`][typed_path::UnixPathBuf].
+///
+/// ```
+/// # use pathbuf::unix_pathbuf;
+/// # use typed_path::UnixPathBuf;
+/// #
+/// let image = "app";
+///
+/// assert_eq!(unix_pathbuf!["/opt", image, "bin"], UnixPathBuf::from("/opt/app/bin"));
+/// assert_eq!(unix_pathbuf!["/opt", r"C:\app"], UnixPathBuf::from(r"/opt/C:\app"));
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+#[cfg(feature = "typed-path")]
+#[macro_export]
+macro_rules! unix_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf_build!(
+            $crate::__private::UnixPathBuf = $crate::__private::UnixPathBuf::new(); []; $($args)*
+        )
+    };
+}
+
+/// Creates a [`WindowsPathBuf`][typed_path::WindowsPathBuf] containing the arguments, on any platform.
+///
+/// `windows_pathbuf!` accepts the same syntax as [`pathbuf!`][pathbuf], but joins the components with `\` and
+/// follows the Windows rules for prefixes like `C:`, `\\?\` and UNC shares: a component with a prefix replaces the
+/// path, while a component with only a root keeps the prefix of the path before it. Components can be anything
+/// implementing [`AsRef<WindowsPath>`][typed_path::WindowsPath], like `&str`, `&[u8]` or another
+/// [`WindowsPathBuf`][typed_path::WindowsPathBuf].
+///
+/// ```
+/// # use pathbuf::windows_pathbuf;
+/// # use typed_path::WindowsPathBuf;
+/// #
+/// let version = "1.2.0";
+///
+/// assert_eq!(
+///     windows_pathbuf![r"C:\Program Files", "App", version],
+///     WindowsPathBuf::from(r"C:\Program Files\App\1.2.0")
+/// );
+/// assert_eq!(windows_pathbuf![r"C:\Users", r"\Temp"], WindowsPathBuf::from(r"C:\Temp"));
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+#[cfg(feature = "typed-path")]
+#[macro_export]
+macro_rules! windows_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf_build!(
+            $crate::__private::WindowsPathBuf = $crate::__private::WindowsPathBuf::new(); []; $($args)*
+        )
+    };
+}
+
+/// Pushes the arguments onto an existing [`PathBuf`][std_path_pathbuf].
+///
+/// The first argument is the buffer, either a [`PathBuf`][std_path_pathbuf] place or a `&mut PathBuf`. The remaining
+/// arguments follow the syntax of [`pathbuf!`][pathbuf], and the exact number of extra bytes is reserved once before
+/// they are pushed. Together with [`pop_path!`][pop_path] this lets a directory walker reuse one buffer:
+///
+/// ```
+/// # use pathbuf::{pathbuf, pop_path, push_path};
+/// # use std::path::PathBuf;
+/// #
+/// let mut scratch = PathBuf::from("repo");
+/// let name = "lib.rs";
+///
+/// push_path!(scratch, "src", name);
+/// assert_eq!(scratch, pathbuf!["repo", "src", "lib.rs"]);
+///
+/// pop_path!(scratch, 2);
+/// assert_eq!(scratch, PathBuf::from("repo"));
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [pop_path]: macro.pop_path.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! push_path {
+    ( $buf:expr $( , $( $args:tt )* )? ) => {{
+        use $crate::__private::PathBufMut as _;
+
+        let buf = $buf.path_buf_mut();
+        *buf = $crate::__pathbuf_build!(std::path::PathBuf = std::mem::take(buf); []; $( $( $args )* )?);
+    }};
+}
+
+/// Removes the last components of a [`PathBuf`][std_path_pathbuf], undoing [`push_path!`][push_path].
+///
+/// The first argument is the buffer, either a [`PathBuf`][std_path_pathbuf] place or a `&mut PathBuf`, and the second
+/// one the number of components to remove. Like [`PathBuf::pop`][std_path_pathbuf_pop], this never reallocates. The
+/// macro evaluates to `false` if the path ran out of components before all of them were removed.
+///
+/// Note that the count is in components, not arguments, so undoing `push_path!(buf, "a/b")` takes two.
+///
+/// [push_path]: macro.push_path.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+/// [std_path_pathbuf_pop]: https://doc.rust-lang.org/std/path/struct.PathBuf.html#method.pop "Documentation for std::path::PathBuf::pop (method)"
+#[macro_export]
+macro_rules! pop_path {
+    ( $buf:expr, $count:expr $(,)? ) => {{
+        use $crate::__private::PathBufMut as _;
+
+        $crate::__private::pop_components($buf.path_buf_mut(), $count)
+    }};
+}
+
+/// Joins string literals at compile time into a [`StaticPath`].
+///
+/// The components are joined with the separator of the target platform, so the result is the same as of
+/// [`pathbuf!`][pathbuf], but without any allocation. The [`StaticPath`] can be stored in constants and statics and
+/// dereferences to a [`Path`][std_path_path]:
+///
+/// ```
+/// # use pathbuf::{path, pathbuf, StaticPath};
+/// # use std::path::Path;
+/// #
+/// const CONFIG: StaticPath = path!("etc", "app", "config.toml");
+///
+/// let config: &'static Path = CONFIG.as_path();
+/// assert_eq!(config, pathbuf!["etc", "app", "config.toml"]);
+/// ```
+///
+/// Only literals are accepted, and unlike in [`pathbuf!`][pathbuf] they are not format templates:
+///
+/// ```compile_fail
+/// # use pathbuf::path;
+/// #
+/// let name = "config.toml";
+/// let config = path!("etc", name);
+/// ```
+///
+/// A component after the first which starts with a separator would replace the path before it, and is rejected at
+/// compile time as well. So are an empty component after the first and a separator at the end of a component before
+/// another, where [`pathbuf!`][pathbuf] adds no separator of its own:
+///
+/// ```compile_fail
+/// # use pathbuf::path;
+/// #
+/// let config = path!("etc/", "app");
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_path]: https://doc.rust-lang.org/std/path/struct.Path.html "Documentation for std::path::Path (struct)"
+#[macro_export]
+macro_rules! path {
+    ( $first:literal $( , $part:literal )* $(,)? ) => {{
+        const { $crate::__private::check_literals(&[$first $( , $part )*]) }
+
+        $crate::StaticPath::__new(concat!($first $( , $crate::__path_separator!(), $part )*))
+    }};
+
+    ( $( $other:tt )* ) => {
+        compile_error!("`path!` only accepts string literals, use `pathbuf!` for other components")
+    };
+}
+
+#[cfg(not(windows))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __path_separator {
+    () => {
+        "/"
+    };
+}
+
+#[cfg(windows)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __path_separator {
+    () => {
+        "\\"
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] like [`pathbuf!`][pathbuf], but rejects path traversal.
+///
+/// Every component after the first is checked before it is pushed, including further components of the first
+/// argument, like the items of a spread. An argument which is absolute, has a root or a prefix, or contains a `..`
+/// segment results in a [`PathBufError`] naming the index of that argument.
+///
+/// ```
+/// # use pathbuf::{try_pathbuf, PathBufError};
+/// # use std::path::PathBuf;
+/// #
+/// assert_eq!(try_pathbuf!["/tmp", "uploads"], Ok(PathBuf::from("/tmp/uploads")));
+/// assert_eq!(try_pathbuf!["/tmp", "..", "etc"], Err(PathBufError::ParentDir { index: 1 }));
+/// ```
+///
+/// A [`PathPolicy`] given before the arguments checks their components against further rules, like NUL bytes,
+/// control characters or the reserved names of Windows. Otherwise, such a component is only rejected by the
+/// filesystem once the path is used, with a less helpful [`io::Error`][std_io_error]:
+///
+/// ```
+/// # use pathbuf::{try_pathbuf, PathBufError, PathPolicy};
+/// #
+/// let name = "report\0.pdf";
+///
+/// assert_eq!(
+///     try_pathbuf![policy = PathPolicy::portable(); "/tmp", name],
+///     Err(PathBufError::Nul { index: 1 })
+/// );
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_io_error]: https://doc.rust-lang.org/std/io/struct.Error.html "Documentation for std::io::Error (struct)"
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! try_pathbuf {
+    ( policy = $policy:expr; $( $args:tt )* ) => {
+        $crate::__pathbuf_build!($crate::__private::Checked = $crate::__private::Checked::with_policy($policy); []; $($args)*).finish()
+    };
+
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf_build!($crate::__private::Checked = $crate::__private::Checked::default(); []; $($args)*).finish()
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] like [`pathbuf!`][pathbuf], expanding environment variables and `~`.
+///
+/// A leading `~` of the first component expands to the home directory, and `$VAR` and `${VAR}` in any argument expand
+/// to the value of the variable, with `$$` for a literal `$`. A variable which is not set results in an
+/// [`ExpandError`] naming it and the index of the argument.
+///
+/// ```
+/// # use pathbuf::{expand_pathbuf, ExpandError};
+/// # use std::path::PathBuf;
+/// #
+/// # #[cfg(unix)]
+/// # {
+/// let cache = expand_pathbuf!["~/.cache", "app"];
+///
+/// assert_eq!(
+///     expand_pathbuf!["$PATHBUF_UNSET_EXAMPLE/app"],
+///     Err(ExpandError::Missing { index: 0, name: String::from("PATHBUF_UNSET_EXAMPLE") }),
+/// );
+/// # }
+/// ```
+///
+/// The variables are read from the process environment, unless another [`Env`][crate::Env] is given before the
+/// arguments, like `expand_pathbuf![env = my_env; "$DIR", "file"]`.
+///
+/// As string literals are format templates, `${VAR}` has to be written as `"${{VAR}}"` or `("${VAR}")` in one.
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! expand_pathbuf {
+    // The `match` keeps a temporary env, like `HashMap::from(...)`, alive until the path is built.
+    ( env = $env:expr; $( $args:tt )* ) => {
+        match &$env {
+            env => $crate::__pathbuf_build!($crate::__private::Expanded<'_> = $crate::__private::Expanded::new(env); []; $($args)*).finish(),
+        }
+    };
+
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf_build!($crate::__private::Expanded<'_> = $crate::__private::Expanded::new(&$crate::__private::ProcessEnv); []; $($args)*).finish()
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] which is guaranteed to stay under a root.
+///
+/// The root comes first and is separated from the remaining arguments by a semicolon. The remaining arguments are
+/// resolved lexically while they are pushed: `.` segments are skipped and `..` segments remove the last component
+/// beneath the root. Climbing above the root, or passing an argument which is absolute, has a root or a prefix,
+/// results in a [`PathBufError`] naming the index of that argument, counting the root as index `0`.
+///
+/// ```
+/// # use pathbuf::{confined_pathbuf, PathBufError};
+/// # use std::path::PathBuf;
+/// #
+/// let upload_dir = "/srv/uploads";
+///
+/// assert_eq!(
+///     confined_pathbuf!(upload_dir; "alice", "./drafts/../report.pdf"),
+///     Ok(PathBuf::from("/srv/uploads/alice/report.pdf"))
+/// );
+/// assert_eq!(
+///     confined_pathbuf!(upload_dir; "alice", "../../etc/passwd"),
+///     Err(PathBufError::EscapesRoot { index: 2 })
+/// );
+/// ```
+///
+/// The check is purely lexical, so a symbolic link beneath the root may still point outside of it.
+///
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! confined_pathbuf {
+    ( $root:expr; $( $args:tt )* ) => {
+        $crate::__pathbuf_build!($crate::__private::Confined = $crate::__private::Confined::default(); []; $root, $($args)*).finish()
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] like [`pathbuf!`][pathbuf], but resolves `.` and `..` lexically.
+///
+/// While the arguments are pushed, `.` segments and redundant separators are dropped, and a `..` segment removes the
+/// component before it. A `..` directly after the root is dropped, as there is nothing above the root, while leading
+/// `..` segments of a relative path are kept. The filesystem is never accessed, so `a/..` is folded even if `a` is a
+/// symbolic link.
+///
+/// ```
+/// # use pathbuf::normalized_pathbuf;
+/// # use std::path::PathBuf;
+/// #
+/// assert_eq!(normalized_pathbuf!["config", "./base/../overrides", "app.toml"], PathBuf::from("config/overrides/app.toml"));
+/// assert_eq!(normalized_pathbuf!["..", "a/../b"], PathBuf::from("../b"));
+/// ```
+///
+/// Like with [`pathbuf!`][pathbuf], an absolute argument replaces the path before it.
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! normalized_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__pathbuf_build!(
+            $crate::__private::Normalized = $crate::__private::Normalized::default(); []; $($args)*
+        )
+        .finish()
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] like [`pathbuf!`][pathbuf] and makes it absolute.
+///
+/// The path is made absolute with [`std::path::absolute`], which uses the current directory but doesn't otherwise
+/// access the filesystem. On failure, the returned [`io::Error`][std::io::Error] wraps a [`ResolveError`] with the
+/// attempted path.
+///
+/// ```
+/// # use pathbuf::absolute_pathbuf;
+/// #
+/// # fn main() -> std::io::Result<()> {
+/// let path = absolute_pathbuf!["target", "debug"]?;
+///
+/// assert!(path.is_absolute());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! absolute_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__private::absolute($crate::__pathbuf_build!(
+            std::path::PathBuf = std::path::PathBuf::new(); []; $($args)*
+        ))
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] like [`pathbuf!`][pathbuf] and canonicalizes it.
+///
+/// The path is canonicalized with [`std::fs::canonicalize`], so it has to exist and symbolic links are resolved. On
+/// failure, the returned [`io::Error`][std::io::Error] keeps its [`ErrorKind`][std::io::ErrorKind] and wraps a
+/// [`ResolveError`] with the attempted path and the index of its first component which does not exist, so the error
+/// message points at the missing directory:
+///
+/// ```
+/// # use pathbuf::canonical_pathbuf;
+/// #
+/// let error = canonical_pathbuf!["does-not-exist", "config.toml"].unwrap_err();
+///
+/// assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+/// assert!(error.to_string().starts_with("failed to resolve `does-not-exist"));
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! canonical_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__private::canonicalize($crate::__pathbuf_build!(
+            std::path::PathBuf = std::path::PathBuf::new(); []; $($args)*
+        ))
+    };
+}
+
+/// Opens the path built from the arguments beneath a [`Root`], returning the resolved path and the open file.
+///
+/// The [`Root`] comes first and is separated from the remaining arguments by a semicolon. The remaining arguments take
+/// the same syntax as in [`pathbuf!`][pathbuf], including the extension clause. They are joined and then opened with
+/// [`Root::open_beneath`], so neither `..` segments nor symbolic links can leave the root.
+///
+/// ```no_run
+/// # use pathbuf::{open_beneath, Root};
+/// #
+/// # fn main() -> std::io::Result<()> {
+/// let root = Root::open("/srv/www")?;
+/// let user_input = "../../etc/passwd";
+///
+/// assert!(open_beneath!(root; "static", user_input).is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+#[cfg(all(unix, feature = "beneath"))]
+#[macro_export]
+macro_rules! open_beneath {
+    ( $root:expr; $( $args:tt )* ) => {
+        $root.open_beneath($crate::__pathbuf_build!(
+            std::path::PathBuf = std::path::PathBuf::new(); []; $($args)*
+        ))
+    };
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::pathbuf;
+    use crate::{PathBufError, PathPolicy, StaticPath};
     use std::path::PathBuf;
 
     #[test]
@@ -93,4 +944,343 @@ mod tests {
 
         assert_eq!(p, expected);
     }
+
+    #[test]
+    fn try_accepts_relative_parts() {
+        let p = try_pathbuf!["hello", "world", "filename.txt",];
+
+        assert_eq!(p, Ok(pathbuf!["hello", "world", "filename.txt"]));
+    }
+
+    #[test]
+    fn try_allows_absolute_first_part() {
+        let p = try_pathbuf!["/tmp", "filename.txt"];
+
+        assert_eq!(p, Ok(pathbuf!["/tmp", "filename.txt"]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn try_rejects_traversal() {
+        assert_eq!(
+            try_pathbuf!["/tmp", "/etc/shadow"],
+            Err(PathBufError::Absolute { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf!["/tmp", "a", "b/../../etc"],
+            Err(PathBufError::ParentDir { index: 2 })
+        );
+    }
+
+    #[test]
+    fn confined_resolves_dots_beneath_root() {
+        let p = confined_pathbuf!("root"; "a/./b", "..", "c",);
+
+        assert_eq!(p, Ok(pathbuf!["root", "a", "c"]));
+    }
+
+    #[test]
+    fn confined_rejects_escaping_root() {
+        assert_eq!(
+            confined_pathbuf!("root"; "a", "../.."),
+            Err(PathBufError::EscapesRoot { index: 2 })
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn confined_rejects_absolute_parts() {
+        assert_eq!(
+            confined_pathbuf!("/srv"; "a", "/etc/shadow"),
+            Err(PathBufError::Absolute { index: 2 })
+        );
+    }
+
+    #[test]
+    fn evaluates_each_part_once() {
+        let mut parts = ["a", "b"].into_iter();
+
+        let p = pathbuf![parts.next().unwrap(), parts.next().unwrap()];
+
+        assert_eq!(p, PathBuf::from("a").join("b"));
+    }
+
+    #[test]
+    fn accepts_owned_parts() {
+        let dir = PathBuf::from("dir");
+        let name = String::from("filename.txt");
+
+        assert_eq!(
+            pathbuf![dir, name],
+            PathBuf::from("dir").join("filename.txt")
+        );
+    }
+
+    #[test]
+    fn allocates_exactly_once() {
+        let long = "x".repeat(100);
+        let p = pathbuf![
+            "hello",
+            &long,
+            String::from("filename.txt"),
+            PathBuf::from("sub")
+        ];
+
+        // The capacity is only ever what was pre-allocated if no push had to grow the buffer.
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn spreads_iterators() {
+        let segments = [String::from("b"), String::from("c")];
+
+        let p = pathbuf!["a", ..segments.iter(), "d", ..Vec::<&str>::new()];
+
+        assert_eq!(p, pathbuf!["a", "b", "c", "d"]);
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn try_reports_index_of_spread_argument() {
+        let segments = ["b", "..", "c"];
+
+        assert_eq!(
+            try_pathbuf!["a", ..segments, "d"],
+            Err(PathBufError::ParentDir { index: 1 })
+        );
+    }
+
+    #[test]
+    fn try_checks_spread_in_first_position() {
+        assert_eq!(
+            try_pathbuf![..["/srv", "uploads"], "a"],
+            Ok(pathbuf!["/srv", "uploads", "a"])
+        );
+        assert_eq!(
+            try_pathbuf![..&["a", "/etc/shadow"]],
+            Err(PathBufError::Absolute { index: 0 })
+        );
+        assert_eq!(
+            try_pathbuf![..["a", "..", ".."], "b"],
+            Err(PathBufError::ParentDir { index: 0 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = PathPolicy::portable(); ..["ok", "con"]],
+            Err(PathBufError::ReservedName { index: 0 })
+        );
+    }
+
+    #[test]
+    fn skips_none_components() {
+        let none: Option<PathBuf> = None;
+
+        let p = pathbuf![?Some("a"), ?none, "b", ?Some(String::from("c"))];
+
+        assert_eq!(p, pathbuf!["a", "b", "c"]);
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn try_counts_skipped_components() {
+        assert_eq!(
+            try_pathbuf!["a", ?None::<&str>, ?Some("/etc")],
+            Err(PathBufError::Absolute { index: 2 })
+        );
+    }
+
+    #[test]
+    fn pushes_conditional_components() {
+        let flag = false;
+
+        let p = pathbuf![
+            if !flag => "a", else String::from("x"),
+            if flag => "y", else String::from("b"),
+            if flag => "z",
+            if flag { "w" } else { "c" },
+        ];
+
+        assert_eq!(p, pathbuf!["a", "b", "c"]);
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn sets_extension() {
+        let name = String::from("archive");
+
+        let p = pathbuf!["dir", if true => name; ext = "tar.gz"];
+
+        assert_eq!(p, pathbuf!["dir", "archive.tar.gz"]);
+        assert_eq!(p.capacity(), p.as_os_str().len());
+        assert_eq!(pathbuf!["a", "b.txt"; ext = ""], pathbuf!["a", "b"]);
+        assert_eq!(
+            pathbuf![if false { "x" } else { "y" }; ext = "md",],
+            pathbuf!["y.md"]
+        );
+    }
+
+    #[test]
+    fn try_rejects_extension_with_separator() {
+        assert_eq!(
+            try_pathbuf!["a", "b"; ext = "x/y"],
+            Err(PathBufError::Extension { index: 2 })
+        );
+        assert_eq!(
+            confined_pathbuf!("root"; "a", ..["b"]; ext = "json"),
+            Ok(pathbuf!["root", "a", "b.json"])
+        );
+    }
+
+    #[test]
+    fn extension_never_renames_the_root() {
+        for input in [".", "", "./."] {
+            assert_eq!(
+                try_pathbuf!["/srv/uploads", input; ext = "json"],
+                Err(PathBufError::NoFileName { index: 2 })
+            );
+            assert_eq!(
+                try_pathbuf![policy = PathPolicy::portable(); "/srv/uploads", input; ext = "json"],
+                Err(PathBufError::NoFileName { index: 2 })
+            );
+            assert_eq!(
+                confined_pathbuf!("/srv/uploads"; input; ext = "json"),
+                Err(PathBufError::NoFileName { index: 2 })
+            );
+        }
+
+        assert_eq!(
+            confined_pathbuf!("root"; "a", ".."; ext = "txt"),
+            Err(PathBufError::NoFileName { index: 3 })
+        );
+        assert_eq!(
+            try_pathbuf!["root"; ext = "txt"],
+            Err(PathBufError::NoFileName { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf!["root", "a", "."; ext = "txt"],
+            Ok(pathbuf!["root", "a.txt"])
+        );
+    }
+
+    #[test]
+    fn formats_literal_components() {
+        let (id, rev) = (42, "b");
+
+        let p = pathbuf!["logs", "{id}-{rev}.log", "{{draft}}", ("{id}"); ext = "gz"];
+
+        assert_eq!(p, pathbuf!["logs", ("42-b.log"), ("{draft}"), ("{id}.gz")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn formatted_components_replace_like_push() {
+        let root = "/etc";
+
+        assert_eq!(
+            pathbuf!["/tmp", "{root}", "shadow"],
+            pathbuf!["/etc/shadow"]
+        );
+        assert_eq!(pathbuf!["", "{root}"], pathbuf!["/etc"]);
+        assert_eq!(
+            try_pathbuf!["/tmp", "{root}"],
+            Err(PathBufError::Absolute { index: 1 })
+        );
+    }
+
+    #[test]
+    fn joins_literals_at_compile_time() {
+        const P: StaticPath = path!("a", "b", "file.txt",);
+        static Q: StaticPath = path!("{a}");
+
+        assert_eq!(P.as_path(), pathbuf!["a", "b", "file.txt"]);
+        assert_eq!(P.as_str().len(), P.as_os_str().len());
+        assert_eq!(Q.to_path_buf(), PathBuf::from("{a}"));
+    }
+
+    #[test]
+    fn pushes_onto_existing_buffer() {
+        let mut p = PathBuf::from("root");
+        let buf = &mut p;
+        let n = 100;
+
+        push_path!(buf, "a", ..["b", "c"], "{n}", ?Some("d"); ext = "txt");
+
+        assert_eq!(p, pathbuf!["root", "a", "b", "c", "100", "d.txt"]);
+        assert_eq!(p.capacity(), p.as_os_str().len());
+
+        let capacity = p.capacity();
+        assert!(pop_path!(p, 4));
+        push_path!(p, "e", "f");
+        assert_eq!(p, pathbuf!["root", "a", "e", "f"]);
+        assert_eq!(p.capacity(), capacity);
+
+        assert!(!pop_path!(p, 5));
+        assert_eq!(p, PathBuf::new());
+    }
+
+    #[cfg(feature = "camino")]
+    #[test]
+    fn builds_utf8_paths() {
+        use camino::{Utf8Path, Utf8PathBuf};
+
+        let segments = vec![String::from("b"), String::from("c")];
+        let name = "d";
+
+        let p = utf8_pathbuf![Utf8Path::new("a"), ..&segments, ?Some(name), "e"; ext = "json"];
+
+        assert_eq!(
+            p,
+            Utf8PathBuf::from(pathbuf!["a", "b", "c", "d", "e.json"].to_str().unwrap())
+        );
+        assert_eq!(p.capacity(), p.as_str().len());
+    }
+
+    #[cfg(feature = "typed-path")]
+    #[test]
+    fn builds_unix_paths_on_any_platform() {
+        use typed_path::UnixPathBuf;
+
+        let name = "app";
+
+        assert_eq!(
+            unix_pathbuf!["/srv", name, ?Some("bin"); ext = "sh"],
+            UnixPathBuf::from("/srv/app/bin.sh")
+        );
+        assert_eq!(
+            unix_pathbuf!["/srv", "/etc", "{name}"],
+            UnixPathBuf::from("/etc/app")
+        );
+        assert_eq!(
+            unix_pathbuf!["srv", r"C:\x"],
+            UnixPathBuf::from(r"srv/C:\x")
+        );
+    }
+
+    #[cfg(feature = "typed-path")]
+    #[test]
+    fn builds_windows_paths_on_any_platform() {
+        use typed_path::WindowsPathBuf;
+
+        assert_eq!(
+            windows_pathbuf![r"C:\Users", "alice", "AppData"],
+            WindowsPathBuf::from(r"C:\Users\alice\AppData")
+        );
+
+        assert_eq!(
+            windows_pathbuf![r"C:\Users", r"D:\Data"],
+            WindowsPathBuf::from(r"D:\Data")
+        );
+        assert_eq!(
+            windows_pathbuf![r"C:\Users", r"\Temp"],
+            WindowsPathBuf::from(r"C:\Temp")
+        );
+        assert_eq!(
+            windows_pathbuf![r"\\server\share", "dir", "file.txt"],
+            WindowsPathBuf::from(r"\\server\share\dir\file.txt")
+        );
+        assert_eq!(
+            windows_pathbuf![r"\\?\C:\very", "long"],
+            WindowsPathBuf::from(r"\\?\C:\very\long")
+        );
+    }
 }
@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Finds the root of the workspace containing the package at `manifest_dir`.
+///
+/// This is the nearest directory at or above `manifest_dir` whose `Cargo.toml` has a `[workspace]` table, or
+/// `manifest_dir` itself if there is none, like for a package outside of a workspace.
+pub fn find_workspace_root(manifest_dir: &Path) -> PathBuf {
+    manifest_dir
+        .ancestors()
+        .find(|dir| {
+            fs::read_to_string(dir.join("Cargo.toml"))
+                .is_ok_and(|manifest| has_workspace(&manifest))
+        })
+        .unwrap_or(manifest_dir)
+        .to_path_buf()
+}
+
+/// Checks whether a manifest has a `[workspace]` table or one of its subtables, like `[workspace.package]`.
+fn has_workspace(manifest: &str) -> bool {
+    // A table header may have whitespace around its keys, like `[ workspace . package ]`.
+    manifest.lines().any(|line| {
+        line.trim()
+            .strip_prefix('[')
+            .and_then(|header| header.trim_start().strip_prefix("workspace"))
+            .map(str::trim_start)
+            .is_some_and(|rest| rest.starts_with(']') || rest.starts_with('.'))
+    })
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] beneath the directory of the calling package's `Cargo.toml`.
+///
+/// The directory is taken from `CARGO_MANIFEST_DIR` when the caller is compiled, and the arguments are pushed like
+/// with [`pathbuf!`][pathbuf]. This is meant for tests and build scripts, as the directory usually doesn't exist
+/// where a binary is deployed.
+///
+/// ```
+/// # use pathbuf::manifest_pathbuf;
+/// #
+/// let name = "lib.rs";
+/// let source = manifest_pathbuf!["src", name];
+///
+/// assert!(source.is_file());
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! manifest_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::pathbuf![std::path::Path::new(env!("CARGO_MANIFEST_DIR")), $($args)*]
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] beneath the root of the calling package's workspace.
+///
+/// The root is the nearest directory at or above `CARGO_MANIFEST_DIR` whose `Cargo.toml` has a `[workspace]` table.
+/// A package outside of a workspace is its own root. The lookup reads from the filesystem once per call site, and
+/// the result is cached for later calls. The arguments are pushed like with [`pathbuf!`][pathbuf].
+///
+/// ```
+/// # use pathbuf::workspace_pathbuf;
+/// #
+/// let manifest = workspace_pathbuf!["Cargo.toml"];
+///
+/// assert!(manifest.is_file());
+/// ```
+///
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! workspace_pathbuf {
+    ( $( $args:tt )* ) => {{
+        static ROOT: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+        let root = ROOT.get_or_init(|| {
+            $crate::__private::find_workspace_root(std::path::Path::new(env!("CARGO_MANIFEST_DIR")))
+        });
+
+        $crate::pathbuf![root, $($args)*]
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_workspace_root, has_workspace};
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn workspace_tables() {
+        assert!(has_workspace("[workspace]\nmembers = []\n"));
+        assert!(has_workspace("[package]\n\n  [workspace.package]\n"));
+        assert!(!has_workspace("[package]\nname = \"workspace\"\n"));
+        assert!(has_workspace("[ workspace ]\n"));
+        assert!(has_workspace("[\tworkspace . package]\n"));
+        assert!(!has_workspace("[workspaces]\n"));
+        assert!(!has_workspace("[ workspaces ]\n"));
+    }
+
+    #[test]
+    fn finds_workspace_root() {
+        let temp = tempfile::tempdir().unwrap();
+        let member = temp.path().join("crates").join("member");
+        fs::create_dir_all(&member).unwrap();
+        fs::write(temp.path().join("Cargo.toml"), "[workspace]\n").unwrap();
+        fs::write(temp.path().join("crates").join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(member.join("Cargo.toml"), "[package]\n").unwrap();
+
+        assert_eq!(find_workspace_root(&member), temp.path());
+    }
+
+    #[test]
+    fn falls_back_to_manifest_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let package = temp.path().join("package");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join("Cargo.toml"), "[package]\n").unwrap();
+
+        assert_eq!(find_workspace_root(&package), package);
+    }
+
+    #[test]
+    fn macros() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(
+            manifest_pathbuf!["src", "lib.rs"],
+            manifest_dir.join("src/lib.rs")
+        );
+        assert_eq!(manifest_pathbuf![], manifest_dir);
+        assert_eq!(
+            workspace_pathbuf!["Cargo.toml"],
+            manifest_dir.join("Cargo.toml")
+        );
+    }
+}
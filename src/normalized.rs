@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::Builder;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+/// Builds the path for [`normalized_pathbuf!`][crate::normalized_pathbuf], folding `.` and `..` while pushing.
+#[derive(Debug, Default)]
+pub struct Normalized {
+    path: PathBuf,
+}
+
+impl Builder for Normalized {
+    type Part = Path;
+
+    fn reserve(&mut self, joined_len: usize) {
+        Builder::reserve(&mut self.path, joined_len);
+    }
+
+    fn push_part(&mut self, part: &Path) {
+        for component in part.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => match self.path.components().next_back() {
+                    Some(Component::Normal(_)) => {
+                        self.path.pop();
+                    }
+                    // There is nothing above the root, so `/..` is `/`.
+                    Some(Component::RootDir) => {}
+                    _ => self.path.push(component),
+                },
+                Component::Prefix(_) | Component::RootDir | Component::Normal(_) => {
+                    self.path.push(component)
+                }
+            }
+        }
+    }
+
+    type Extension = OsStr;
+
+    fn extension_len(extension: &OsStr) -> usize {
+        extension.len()
+    }
+
+    fn set_extension(&mut self, extension: &OsStr) {
+        self.path.set_extension(extension);
+    }
+}
+
+impl Normalized {
+    pub fn finish(self) -> PathBuf {
+        self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{normalized_pathbuf, pathbuf};
+    use proptest::prelude::*;
+    use std::path::{Component, Path, PathBuf};
+
+    /// Folds the components of an already joined path with a stack.
+    fn reference(path: &Path) -> PathBuf {
+        let mut stack = Vec::new();
+
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => match stack.last() {
+                    Some(Component::Normal(_)) => {
+                        stack.pop();
+                    }
+                    Some(Component::RootDir) => {}
+                    _ => stack.push(component),
+                },
+                _ => stack.push(component),
+            }
+        }
+
+        stack.iter().collect()
+    }
+
+    fn part() -> impl Strategy<Value = String> {
+        let segment = prop::sample::select(vec!["a", "bc", ".", "..", ""]);
+        let separator = prop::sample::select(vec!["/", "//"]);
+
+        (
+            any::<bool>(),
+            prop::collection::vec((segment, separator), 0..4),
+        )
+            .prop_map(|(absolute, segments)| {
+                let mut part = String::from(if absolute { "/" } else { "" });
+                for (segment, separator) in segments {
+                    part.push_str(segment);
+                    part.push_str(separator);
+                }
+                part
+            })
+    }
+
+    #[test]
+    fn folds_dots() {
+        assert_eq!(
+            normalized_pathbuf!["a/./b", "../c//d/"],
+            pathbuf!["a", "c", "d"]
+        );
+        assert_eq!(
+            normalized_pathbuf!["..", "a", "../../b"],
+            PathBuf::from("../../b")
+        );
+        assert_eq!(normalized_pathbuf!["a", "..", "."], PathBuf::new());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn drops_parent_of_root() {
+        assert_eq!(normalized_pathbuf!["/", "../a", "..", ".."], pathbuf!["/"]);
+        assert_eq!(normalized_pathbuf!["a/b", "/c/../d"], pathbuf!["/d"]);
+    }
+
+    proptest! {
+        #[test]
+        fn matches_reference(parts in prop::collection::vec(part(), 0..6)) {
+            let normalized = normalized_pathbuf![..&parts];
+
+            prop_assert_eq!(&normalized, &reference(&pathbuf![..&parts]));
+            prop_assert_eq!(normalized.components().collect::<PathBuf>(), normalized.clone());
+            prop_assert!(normalized.components().all(|c| c != Component::CurDir));
+        }
+    }
+}
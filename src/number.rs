@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kind::{ForeignKind, IntoPart};
+use crate::part::{PartWriter, PathPart};
+use std::fmt::{self, Display, Formatter, Write};
+
+/// A primitive integer which can be pushed as a path component.
+///
+/// This is implemented for every signed and unsigned primitive integer, and cannot be implemented outside of this
+/// crate.
+pub trait Integer: Copy + Display + sealed::Sealed {
+    /// Returns the number of bytes of the decimal representation, including the sign.
+    fn decimal_len(self) -> usize;
+}
+
+mod sealed {
+    use std::fmt;
+
+    pub trait Sealed {
+        /// Writes the sign, `zeros` leading zeros and then the digits.
+        fn write_padded(self, zeros: usize, f: &mut dyn fmt::Write) -> fmt::Result;
+    }
+}
+
+/// Writes `count` zeros, in chunks rather than one at a time.
+fn write_zeros(mut count: usize, f: &mut dyn Write) -> fmt::Result {
+    const ZEROS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+    while count > 0 {
+        let chunk = count.min(ZEROS.len());
+        f.write_str(&ZEROS[..chunk])?;
+        count -= chunk;
+    }
+
+    Ok(())
+}
+
+macro_rules! impl_integer {
+    ( $( $unsigned:ty ),* ; $( $signed:ty ),* ) => {
+        $(
+            impl sealed::Sealed for $unsigned {
+                fn write_padded(self, zeros: usize, f: &mut dyn Write) -> fmt::Result {
+                    write_zeros(zeros, f)?;
+                    write!(f, "{self}")
+                }
+            }
+
+            impl Integer for $unsigned {
+                fn decimal_len(self) -> usize {
+                    self.checked_ilog10().map_or(1, |log| log as usize + 1)
+                }
+            }
+        )*
+
+        $(
+            impl sealed::Sealed for $signed {
+                fn write_padded(self, zeros: usize, f: &mut dyn Write) -> fmt::Result {
+                    if self < 0 {
+                        f.write_char('-')?;
+                    }
+
+                    self.unsigned_abs().write_padded(zeros, f)
+                }
+            }
+
+            impl Integer for $signed {
+                fn decimal_len(self) -> usize {
+                    usize::from(self < 0) + self.unsigned_abs().decimal_len()
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize; i8, i16, i32, i64, i128, isize);
+
+/// Pads an integer component with leading zeros to at least `width` bytes, like `pad(4, shard)` for `0042`.
+///
+/// The rules are those of [`format!("{:0width$}")`][std_format]:
+///
+/// - A value with more digits than `width` is written in full, it is never truncated.
+/// - A negative value keeps its `-` in front of the zeros, and the sign counts towards the width, so `pad(4, -7)` is
+///   `-007`.
+///
+/// Unlike with `format!`, the width is not limited to [`u16::MAX`].
+///
+/// The digits are formatted straight into the buffer of a [`PathBuf`][std_path_pathbuf], without an intermediate
+/// [`String`].
+///
+/// ```
+/// # use pathbuf::{pad, pathbuf};
+/// # use std::path::PathBuf;
+/// #
+/// let (shard, segment) = (42_u16, 17_u64);
+///
+/// assert_eq!(
+///     pathbuf!["data", pad(4, shard), "segment-{segment:06}"; ext = "bin"],
+///     PathBuf::from("data/0042/segment-000017.bin"),
+/// );
+/// ```
+///
+/// [std_format]: https://doc.rust-lang.org/std/fmt/index.html#width "Documentation for std::fmt (width)"
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+pub fn pad<N: Integer>(width: usize, value: N) -> Padded<N> {
+    Padded { width, value }
+}
+
+/// An integer component padded with leading zeros, returned by [`pad`].
+///
+/// It implements [`Display`], so the padded integer can be turned into a [`String`] as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Padded<N> {
+    width: usize,
+    value: N,
+}
+
+impl<P: ?Sized, N: Integer> PathPart<P> for Padded<N> {
+    fn push_to(&self, path: &mut PartWriter<'_, P>) {
+        path.push_fmt(format_args!("{self}"));
+    }
+
+    fn len_hint(&self) -> usize {
+        self.width.max(self.value.decimal_len())
+    }
+}
+
+impl<N: Integer> Display for Padded<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let zeros = self.width.saturating_sub(self.value.decimal_len());
+        self.value.write_padded(zeros, f)
+    }
+}
+
+impl<N: Integer> ForeignKind for N {}
+
+impl<N: Integer> ForeignKind for Option<N> {}
+
+impl<N: Integer> IntoPart for N {
+    type Part = Padded<N>;
+
+    fn into_part(self) -> Padded<N> {
+        pad(0, self)
+    }
+}
+
+impl<N: Integer> IntoPart for Option<N> {
+    type Part = Option<Padded<N>>;
+
+    fn into_part(self) -> Option<Padded<N>> {
+        self.map(|value| pad(0, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pad, Integer};
+    use crate::pathbuf;
+    use std::path::PathBuf;
+
+    #[test]
+    fn decimal_len() {
+        assert_eq!(0_u8.decimal_len(), 1);
+        assert_eq!(9_u32.decimal_len(), 1);
+        assert_eq!(10_u32.decimal_len(), 2);
+        assert_eq!(u64::MAX.decimal_len(), u64::MAX.to_string().len());
+        assert_eq!((-1_i8).decimal_len(), 2);
+        assert_eq!(i128::MIN.decimal_len(), i128::MIN.to_string().len());
+    }
+
+    #[test]
+    fn integer_components() {
+        let (shard, segment) = (42_u16, 17_u64);
+        let p = pathbuf!["data", shard, segment];
+        assert_eq!(p, PathBuf::from("data/42/17"));
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn padded_components() {
+        let (shard, segment) = (42_u16, 17_u64);
+        let p = pathbuf!["data", pad(4, shard), "segment-{segment:06}"; ext = "bin"];
+        assert_eq!(p, PathBuf::from("data/0042/segment-000017.bin"));
+    }
+
+    #[test]
+    fn padding_never_truncates() {
+        let p = pathbuf!["data", pad(2, 12345_u32)];
+        assert_eq!(p, PathBuf::from("data/12345"));
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn negative_values() {
+        let offset = -7_i32;
+        assert_eq!(pathbuf!["data", offset], PathBuf::from("data/-7"));
+        assert_eq!(pathbuf!["data", pad(4, offset)], PathBuf::from("data/-007"));
+        assert_eq!(
+            pathbuf!["data", pad(4, i8::MIN)],
+            PathBuf::from("data/-128")
+        );
+    }
+
+    #[test]
+    fn widths_beyond_format() {
+        let width = usize::from(u16::MAX) + 2;
+        let p = pathbuf!["a", pad(width, 1_u8), pad(width, -1_i8)];
+        let name = |sign: &str| format!("{sign}{}1", "0".repeat(width - 1 - sign.len()));
+
+        assert_eq!(p, PathBuf::from("a").join(name("")).join(name("-")));
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn integers_in_every_form() {
+        let (shard, replica, primary) = (3_u8, Some(1_u32), false);
+        let shards = [pad(2, 1_u8), pad(2, 2_u8)];
+
+        let p = pathbuf![..shards, shard, ?replica, if primary => "primary", else shard + 1];
+        assert_eq!(p, PathBuf::from("01/02/3/1/4"));
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[cfg(feature = "camino")]
+    #[test]
+    fn utf8_integers() {
+        use crate::utf8_pathbuf;
+        use camino::Utf8PathBuf;
+
+        let shard = 42_usize;
+        assert_eq!(
+            utf8_pathbuf!["data", pad(4, shard)],
+            Utf8PathBuf::from("data/0042")
+        );
+    }
+}
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::Builder;
+use std::fmt;
+use std::path::Path;
+
+/// A value which can be passed as a component to [`pathbuf!`][crate::pathbuf] and its siblings.
+///
+/// Everything implementing [`AsRef<Path>`] is a `PathPart`, and so are the UTF-8 and platform specific path types
+/// with the `camino` and `typed-path` features. Implementing it for a domain type lets it be passed directly, and it
+/// may push any number of components:
+///
+/// ```
+/// # use pathbuf::{pathbuf, PartWriter, PathPart};
+/// # use std::path::PathBuf;
+/// #
+/// struct Date {
+///     year: u16,
+///     month: u8,
+///     day: u8,
+/// }
+///
+/// impl PathPart for Date {
+///     fn push_to(&self, path: &mut PartWriter<'_>) {
+///         path.push_fmt(format_args!("{:04}", self.year));
+///         path.push_fmt(format_args!("{:02}", self.month));
+///         path.push_fmt(format_args!("{:02}", self.day));
+///     }
+///
+///     fn len_hint(&self) -> usize {
+///         "YYYY/MM/DD".len()
+///     }
+/// }
+///
+/// let date = Date { year: 2024, month: 3, day: 9 };
+///
+/// assert_eq!(pathbuf!["logs", date, "app.log"], PathBuf::from("logs/2024/03/09/app.log"));
+/// ```
+///
+/// The type parameter is the borrowed path type of the built path, so implementing `PathPart<Utf8Path>` makes a type
+/// usable with [`utf8_pathbuf!`][crate::utf8_pathbuf] as well. Arguments are taken by value, so implement it for
+/// `&T` too if borrowed values should be accepted.
+///
+/// As every type implementing [`AsRef<Path>`] is covered, the orphan rule keeps other crates from implementing
+/// `PathPart` for a type they do not own. Wrap such a type in a newtype instead, like `Date` above wraps its fields.
+/// With the `chrono` feature, a [`chrono::NaiveDate`] is pushed as `YYYY/MM/DD` without a wrapper.
+///
+/// For enums mapping each variant to a folder name, the `derive` feature provides `#[derive(PathPart)]`. Variants are
+/// pushed as their snake_case name, unless renamed with `#[path_part(rename = "...")]`:
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// # use pathbuf::{pathbuf, PathPart};
+/// # use std::path::PathBuf;
+/// #
+/// #[derive(PathPart)]
+/// enum Service {
+///     HttpServer,
+///     #[path_part(rename = "db")]
+///     Database,
+/// }
+///
+/// assert_eq!(pathbuf!["/var/log", Service::HttpServer], PathBuf::from("/var/log/http_server"));
+/// assert_eq!(pathbuf!["/var/log", Service::Database], PathBuf::from("/var/log/db"));
+/// # }
+/// ```
+pub trait PathPart<P: ?Sized = Path> {
+    /// Pushes the components of this part.
+    fn push_to(&self, path: &mut PartWriter<'_, P>);
+
+    /// Returns an estimate of the number of bytes this part adds, used to pre-allocate the path.
+    ///
+    /// Separators between the components of this part are included, but not the one before it. Defaults to `0`.
+    ///
+    /// The estimate never has to be exact. Lengths are summed with saturating arithmetic, and a capacity which
+    /// cannot be reserved is skipped, so even `usize::MAX` is only a hint and never panics.
+    fn len_hint(&self) -> usize {
+        0
+    }
+}
+
+impl<T: AsRef<Path> + ?Sized> PathPart for T {
+    fn push_to(&self, path: &mut PartWriter<'_>) {
+        path.push(self.as_ref());
+    }
+
+    fn len_hint(&self) -> usize {
+        self.as_ref().as_os_str().len()
+    }
+}
+
+/// Receives the components of a [`PathPart`] and pushes them onto the path being built.
+pub struct PartWriter<'a, P: ?Sized = Path> {
+    target: &'a mut dyn Target<P>,
+}
+
+impl<'a, P: ?Sized> PartWriter<'a, P> {
+    pub(crate) fn new<B: Builder<Part = P>>(builder: &'a mut B) -> Self {
+        PartWriter { target: builder }
+    }
+
+    /// Pushes a component, following the rules of the macro it was passed to.
+    pub fn push<Q: AsRef<P> + ?Sized>(&mut self, part: &Q) {
+        self.target.push_part(part.as_ref());
+    }
+
+    /// Pushes a formatted component, writing it straight into the path where possible.
+    pub fn push_fmt(&mut self, args: fmt::Arguments<'_>) {
+        self.target.push_fmt(args);
+    }
+}
+
+/// The object safe part of [`Builder`] which a [`PartWriter`] pushes onto.
+trait Target<P: ?Sized> {
+    fn push_part(&mut self, part: &P);
+
+    fn push_fmt(&mut self, args: fmt::Arguments<'_>);
+}
+
+impl<B: Builder> Target<B::Part> for B {
+    fn push_part(&mut self, part: &B::Part) {
+        Builder::push_part(self, part);
+    }
+
+    fn push_fmt(&mut self, args: fmt::Arguments<'_>) {
+        Builder::push_fmt(self, args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pathbuf, try_pathbuf, PartWriter, PathPart};
+    use std::path::PathBuf;
+
+    struct TenantId(u32);
+
+    impl PathPart for TenantId {
+        fn push_to(&self, path: &mut PartWriter<'_>) {
+            path.push_fmt(format_args!("tenant-{}", self.0));
+        }
+    }
+
+    struct Date(u16, u8, u8);
+
+    impl PathPart for Date {
+        fn push_to(&self, path: &mut PartWriter<'_>) {
+            path.push_fmt(format_args!("{:04}", self.0));
+            path.push_fmt(format_args!("{:02}", self.1));
+            path.push_fmt(format_args!("{:02}", self.2));
+        }
+
+        fn len_hint(&self) -> usize {
+            "YYYY/MM/DD".len()
+        }
+    }
+
+    #[test]
+    fn custom_part() {
+        let p = pathbuf!["data", TenantId(7), "index.json"];
+        assert_eq!(p, PathBuf::from("data/tenant-7/index.json"));
+    }
+
+    #[test]
+    fn multiple_components() {
+        let p = pathbuf!["logs", Date(2024, 3, 9), "app.log"];
+        assert_eq!(p, PathBuf::from("logs/2024/03/09/app.log"));
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn custom_parts_in_every_form() {
+        let tenants = [TenantId(1), TenantId(2)];
+        let dated = true;
+        let date = dated.then_some(Date(2024, 1, 31));
+
+        let p = pathbuf![..tenants, ?date, if !dated => TenantId(3), else "dated"];
+        assert_eq!(p, PathBuf::from("tenant-1/tenant-2/2024/01/31/dated"));
+    }
+
+    /// Claims to be as long as possible, which must never make building the path panic.
+    struct Huge;
+
+    impl PathPart for Huge {
+        fn push_to(&self, path: &mut PartWriter<'_>) {
+            path.push("huge");
+        }
+
+        fn len_hint(&self) -> usize {
+            usize::MAX
+        }
+    }
+
+    #[test]
+    fn len_hint_saturates() {
+        let p =
+            pathbuf!["a", Huge, ..[Huge, Huge], ?Some(Huge), if true => Huge, else "b"; ext = "x"];
+        assert_eq!(p, PathBuf::from("a/huge/huge/huge/huge/huge.x"));
+        assert_eq!(
+            try_pathbuf!["a", Huge, Huge],
+            Ok(PathBuf::from("a/huge/huge"))
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    mod derive {
+        use crate::{pathbuf, PathPart};
+        use std::path::PathBuf;
+
+        #[derive(Clone, Copy, PathPart)]
+        enum Service {
+            HttpServer,
+            Worker,
+            #[path_part(rename = "db")]
+            Database,
+        }
+
+        #[test]
+        fn snake_case_variants() {
+            assert_eq!(
+                pathbuf!["srv", Service::HttpServer],
+                PathBuf::from("srv/http_server")
+            );
+            assert_eq!(
+                pathbuf!["srv", Service::Worker],
+                PathBuf::from("srv/worker")
+            );
+        }
+
+        #[test]
+        fn renamed_variant() {
+            assert_eq!(pathbuf!["srv", Service::Database], PathBuf::from("srv/db"));
+        }
+
+        #[test]
+        fn spread_borrowed_variants() {
+            let services = [Service::Worker, Service::Database];
+            let p = pathbuf!["srv", ..services.iter()];
+            assert_eq!(p, PathBuf::from("srv/worker/db"));
+            assert_eq!(p.capacity(), p.as_os_str().len());
+        }
+
+        #[cfg(feature = "camino")]
+        #[test]
+        fn utf8_variant() {
+            use crate::utf8_pathbuf;
+            use camino::Utf8PathBuf;
+
+            assert_eq!(
+                utf8_pathbuf!["srv", Service::HttpServer],
+                Utf8PathBuf::from("srv/http_server")
+            );
+        }
+    }
+}
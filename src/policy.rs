@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::PathBufError;
+use std::ffi::OsStr;
+use std::path::{Component, Path};
+
+/// The characters which Windows does not allow in a file name, besides the separators.
+pub(crate) const RESERVED_CHARS: &[u8] = b"<>:\"|?*";
+
+/// The rules which [`try_pathbuf!`][crate::try_pathbuf] checks every component against when given a policy.
+///
+/// [`PathPolicy::new`] only rejects NUL bytes, which no platform allows in a path. [`PathPolicy::portable`] rejects
+/// everything which is not valid on Windows or Unix. Each rule can be turned on or off from there:
+///
+/// ```
+/// # use pathbuf::{try_pathbuf, PathBufError, PathPolicy};
+/// #
+/// const POLICY: PathPolicy = PathPolicy::portable().max_component_len(Some(64));
+///
+/// let title = "aux";
+///
+/// assert_eq!(
+///     try_pathbuf![policy = POLICY; "uploads", title, "notes.txt"],
+///     Err(PathBufError::ReservedName { index: 1 })
+/// );
+/// ```
+///
+/// The rules apply to every component after the first, and to the file name after the extension is set. Like the
+/// checks for path traversal, only the first component is trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathPolicy {
+    nul: bool,
+    control_chars: bool,
+    reserved_chars: bool,
+    reserved_names: bool,
+    trailing_dot_space: bool,
+    max_component_len: Option<usize>,
+}
+
+impl PathPolicy {
+    /// Creates a policy which only rejects NUL bytes.
+    pub const fn new() -> Self {
+        PathPolicy {
+            nul: true,
+            control_chars: false,
+            reserved_chars: false,
+            reserved_names: false,
+            trailing_dot_space: false,
+            max_component_len: None,
+        }
+    }
+
+    /// Creates a policy which rejects every component that is not valid on both Windows and Unix, with components
+    /// of at most 255 bytes.
+    pub const fn portable() -> Self {
+        PathPolicy {
+            nul: true,
+            control_chars: true,
+            reserved_chars: true,
+            reserved_names: true,
+            trailing_dot_space: true,
+            max_component_len: Some(255),
+        }
+    }
+
+    /// Sets whether a NUL byte is rejected.
+    pub const fn nul(mut self, reject: bool) -> Self {
+        self.nul = reject;
+        self
+    }
+
+    /// Sets whether the control characters `\x01` to `\x1f` and `\x7f` are rejected.
+    pub const fn control_chars(mut self, reject: bool) -> Self {
+        self.control_chars = reject;
+        self
+    }
+
+    /// Sets whether the characters `<>:"|?*`, which Windows does not allow in a file name, are rejected.
+    ///
+    /// A `\` is rejected as well, as it is a separator on Windows but can be part of a file name on Unix.
+    pub const fn reserved_chars(mut self, reject: bool) -> Self {
+        self.reserved_chars = reject;
+        self
+    }
+
+    /// Sets whether the device names of Windows, like `CON`, `NUL`, `COM1` or `CONIN$`, are rejected.
+    ///
+    /// They are matched regardless of case and extension, so `con.txt` is rejected as well.
+    pub const fn reserved_names(mut self, reject: bool) -> Self {
+        self.reserved_names = reject;
+        self
+    }
+
+    /// Sets whether a component ending with a `.` or a space is rejected, which Windows silently strips.
+    pub const fn trailing_dot_space(mut self, reject: bool) -> Self {
+        self.trailing_dot_space = reject;
+        self
+    }
+
+    /// Sets the maximum length of a component in bytes, or `None` for no limit.
+    pub const fn max_component_len(mut self, max: Option<usize>) -> Self {
+        self.max_component_len = max;
+        self
+    }
+
+    /// Checks every normal component of the argument at `index`.
+    pub(crate) fn check(&self, index: usize, part: &Path) -> Result<(), PathBufError> {
+        for component in part.components() {
+            if let Component::Normal(name) = component {
+                self.check_name(index, name)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_name(&self, index: usize, name: &OsStr) -> Result<(), PathBufError> {
+        let bytes = name.as_encoded_bytes();
+
+        if self.nul && bytes.contains(&0) {
+            return Err(PathBufError::Nul { index });
+        }
+
+        if self.control_chars && bytes.iter().any(|&byte| is_control(byte)) {
+            return Err(PathBufError::ControlChar { index });
+        }
+
+        if self.reserved_chars
+            && bytes
+                .iter()
+                .any(|&byte| byte == b'\\' || RESERVED_CHARS.contains(&byte))
+        {
+            return Err(PathBufError::ReservedChar { index });
+        }
+
+        if self.reserved_names && is_reserved_name(bytes) {
+            return Err(PathBufError::ReservedName { index });
+        }
+
+        if self.trailing_dot_space && (bytes.ends_with(b".") || bytes.ends_with(b" ")) {
+            return Err(PathBufError::TrailingDotOrSpace { index });
+        }
+
+        if self.max_component_len.is_some_and(|max| bytes.len() > max) {
+            return Err(PathBufError::TooLong { index });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for PathPolicy {
+    fn default() -> Self {
+        PathPolicy::new()
+    }
+}
+
+/// Checks for the control characters which Windows does not allow, leaving NUL to its own rule.
+pub(crate) fn is_control(byte: u8) -> bool {
+    matches!(byte, 0x01..=0x1f | 0x7f)
+}
+
+/// Checks whether a file name is a device name of Windows, ignoring case, the extension and trailing spaces.
+pub(crate) fn is_reserved_name(name: &[u8]) -> bool {
+    let stem = name.split(|&byte| byte == b'.').next().unwrap_or_default();
+    let stem = stem.trim_ascii_end();
+
+    let upper = |name: [u8; 3]| name.map(|byte| byte.to_ascii_uppercase());
+
+    match *stem {
+        [a, b, c] => matches!(&upper([a, b, c]), b"CON" | b"PRN" | b"AUX" | b"NUL"),
+        [a, b, c, b'1'..=b'9'] => matches!(&upper([a, b, c]), b"COM" | b"LPT"),
+        _ => stem.eq_ignore_ascii_case(b"CONIN$") || stem.eq_ignore_ascii_case(b"CONOUT$"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_reserved_name, PathPolicy};
+    use crate::{pathbuf, try_pathbuf, PathBufError};
+
+    #[test]
+    fn reserved_names() {
+        for name in [
+            "CON",
+            "nul",
+            "Aux.txt",
+            "com1",
+            "LPT9.tar.gz",
+            "prn ",
+            "con .log",
+            "CONIN$",
+            "conout$.txt",
+        ] {
+            assert!(is_reserved_name(name.as_bytes()), "{name}");
+        }
+
+        for name in [
+            "CONSOLE", "com0", "lpt", "nul_", "xcon", ".con", "", "CONIN", "conout$x",
+        ] {
+            assert!(!is_reserved_name(name.as_bytes()), "{name}");
+        }
+    }
+
+    #[test]
+    fn default_rejects_nul_only() {
+        let policy = PathPolicy::default();
+
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "report\0.pdf"],
+            Err(PathBufError::Nul { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "con", "a?b. "],
+            Ok(pathbuf!["data", "con", "a?b. "])
+        );
+    }
+
+    #[test]
+    fn portable() {
+        let policy = PathPolicy::portable();
+
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "tab\there"],
+            Err(PathBufError::ControlChar { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "a", "what?"],
+            Err(PathBufError::ReservedChar { index: 2 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "ok/COM3.log"],
+            Err(PathBufError::ReservedName { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "draft."],
+            Err(PathBufError::TrailingDotOrSpace { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "x".repeat(256)],
+            Err(PathBufError::TooLong { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "x".repeat(255), "ok.txt"],
+            Ok(pathbuf!["data", "x".repeat(255), "ok.txt"])
+        );
+    }
+
+    /// A `\\` is a separator on Windows, so only Unix sees it inside a component.
+    #[cfg(unix)]
+    #[test]
+    fn backslash_is_reserved() {
+        assert_eq!(
+            try_pathbuf![policy = PathPolicy::portable(); "data", "a\\b"],
+            Err(PathBufError::ReservedChar { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = PathPolicy::portable().reserved_chars(false); "data", "a\\b"],
+            Ok(std::path::PathBuf::from("data/a\\b"))
+        );
+    }
+
+    #[test]
+    fn first_argument_is_trusted() {
+        let policy = PathPolicy::portable();
+
+        assert_eq!(
+            try_pathbuf![policy = policy; "data?", "ok"],
+            Ok(pathbuf!["data?", "ok"])
+        );
+    }
+
+    #[test]
+    fn extension() {
+        let policy = PathPolicy::portable();
+
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "notes"; ext = "txt "],
+            Err(PathBufError::TrailingDotOrSpace { index: 2 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "notes"; ext = "txt"],
+            Ok(pathbuf!["data", "notes.txt"])
+        );
+    }
+
+    #[test]
+    fn rules_can_be_toggled() {
+        let policy = PathPolicy::portable()
+            .reserved_names(false)
+            .trailing_dot_space(false)
+            .max_component_len(Some(4));
+
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "con", "a."],
+            Ok(pathbuf!["data", "con", "a."])
+        );
+        assert_eq!(
+            try_pathbuf![policy = policy; "data", "notes"],
+            Err(PathBufError::TooLong { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![policy = PathPolicy::new().nul(false); "data", "a\0b"],
+            Ok(pathbuf!["data", "a\0b"])
+        );
+    }
+
+    #[test]
+    fn traversal_is_checked_first() {
+        assert_eq!(
+            try_pathbuf![policy = PathPolicy::portable(); "data", "../con"],
+            Err(PathBufError::ParentDir { index: 1 })
+        );
+    }
+}
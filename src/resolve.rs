@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The error inside the [`io::Error`] of [`absolute_pathbuf!`][crate::absolute_pathbuf] and
+/// [`canonical_pathbuf!`][crate::canonical_pathbuf].
+///
+/// It keeps the path which failed to resolve and the first of its components which does not exist, and can be
+/// reached through [`io::Error::get_ref`]. The [`io::ErrorKind`] is the one of the underlying error.
+///
+/// ```
+/// # use pathbuf::{canonical_pathbuf, ResolveError};
+/// #
+/// let error = canonical_pathbuf!["does-not-exist", "file.txt"].unwrap_err();
+/// let resolve = error.get_ref().and_then(|e| e.downcast_ref::<ResolveError>()).unwrap();
+///
+/// assert_eq!(resolve.missing_index(), Some(0));
+/// ```
+#[derive(Debug)]
+pub struct ResolveError {
+    path: PathBuf,
+    missing_index: Option<usize>,
+    source: io::Error,
+}
+
+impl ResolveError {
+    /// Returns the path which failed to resolve.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the zero-based index of the first component of [`ResolveError::path`] which does not exist.
+    pub fn missing_index(&self) -> Option<usize> {
+        self.missing_index
+    }
+
+    /// Returns the path up to and including the first component which does not exist.
+    pub fn missing_path(&self) -> Option<PathBuf> {
+        self.missing_index
+            .map(|index| self.path.components().take(index + 1).collect())
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to resolve `{}`", self.path.display())?;
+
+        if let Some(missing) = self.missing_path() {
+            write!(f, ", `{}` does not exist", missing.display())?;
+        }
+
+        write!(f, ": {}", self.source)
+    }
+}
+
+impl Error for ResolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Wraps `source` into a [`ResolveError`] for `path`, keeping its [`io::ErrorKind`].
+fn enrich(path: PathBuf, source: io::Error) -> io::Error {
+    let missing_index = match source.kind() {
+        io::ErrorKind::NotFound => first_missing(&path),
+        _ => None,
+    };
+
+    io::Error::new(
+        source.kind(),
+        ResolveError {
+            path,
+            missing_index,
+            source,
+        },
+    )
+}
+
+fn first_missing(path: &Path) -> Option<usize> {
+    let mut prefix = PathBuf::with_capacity(path.as_os_str().len());
+
+    path.components().position(|component| {
+        prefix.push(component);
+        matches!(fs::symlink_metadata(&prefix), Err(error) if error.kind() == io::ErrorKind::NotFound)
+    })
+}
+
+pub fn absolute(path: PathBuf) -> io::Result<PathBuf> {
+    std::path::absolute(&path).map_err(|error| enrich(path, error))
+}
+
+pub fn canonicalize(path: PathBuf) -> io::Result<PathBuf> {
+    fs::canonicalize(&path).map_err(|error| enrich(path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResolveError;
+    use crate::{absolute_pathbuf, canonical_pathbuf};
+    use std::fs;
+    use std::io;
+
+    fn resolve_error(error: &io::Error) -> &ResolveError {
+        error.get_ref().unwrap().downcast_ref().unwrap()
+    }
+
+    #[test]
+    fn canonicalizes_existing_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join("dir")).unwrap();
+
+        let path = canonical_pathbuf![tmp.path(), "dir", ".", "..", "dir"].unwrap();
+
+        assert_eq!(path, fs::canonicalize(tmp.path()).unwrap().join("dir"));
+    }
+
+    #[test]
+    fn reports_first_missing_component() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir(tmp.path().join("dir")).unwrap();
+        let index = tmp.path().components().count() + 1;
+
+        let error = canonical_pathbuf![tmp.path(), "dir", "missing", "file.txt"].unwrap_err();
+        let resolve = resolve_error(&error);
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(resolve.path(), tmp.path().join("dir/missing/file.txt"));
+        assert_eq!(resolve.missing_index(), Some(index));
+        assert_eq!(resolve.missing_path(), Some(tmp.path().join("dir/missing")));
+        assert!(error.to_string().contains("missing` does not exist"));
+    }
+
+    #[test]
+    fn makes_paths_absolute_without_touching_them() {
+        let path = absolute_pathbuf!["does-not-exist", "file.txt"].unwrap();
+
+        assert!(path.is_absolute());
+        assert!(path.ends_with("does-not-exist/file.txt"));
+
+        let error = absolute_pathbuf![].unwrap_err();
+        assert_eq!(resolve_error(&error).path(), std::path::Path::new(""));
+    }
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::part::{PartWriter, PathPart};
+use crate::policy::{is_reserved_name, RESERVED_CHARS};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+
+/// The character which replaces everything that is not allowed in a file name.
+const REPLACEMENT: char = '_';
+
+/// Turns untrusted input, like the title of an upload, into a single file name, like `sanitize(title)`.
+///
+/// Where [`try_pathbuf!`][crate::try_pathbuf] rejects such input, `sanitize` replaces what is not allowed:
+///
+/// - Separators, NUL, control characters and the characters `<>:"|?*` are replaced with `_`.
+/// - Trailing dots and spaces, which Windows strips, are removed.
+/// - A device name of Windows, like `CON` or `com1.txt`, is prefixed with `_`.
+/// - The name is truncated to at most 255 bytes, or the limit set with [`Sanitized::max_len`], without splitting a
+///   character.
+/// - An empty name, or one which is nothing but dots, becomes `_`.
+///
+/// So the result is always exactly one normal component, on every platform, and can neither replace nor climb out
+/// of the path before it:
+///
+/// ```
+/// # use pathbuf::{pathbuf, sanitize};
+/// # use std::path::PathBuf;
+/// #
+/// let title = "Q3/Q4 report: draft?";
+///
+/// assert_eq!(
+///     pathbuf!["uploads", sanitize(title); ext = "pdf"],
+///     PathBuf::from("uploads/Q3_Q4 report_ draft_.pdf"),
+/// );
+/// assert_eq!(pathbuf!["uploads", sanitize("..")], PathBuf::from("uploads/_"));
+/// ```
+pub fn sanitize(name: &str) -> Sanitized<'_> {
+    Sanitized { name, max_len: 255 }
+}
+
+/// A file name made safe from untrusted input, returned by [`sanitize`].
+///
+/// It implements [`Display`], so the name can be turned into a [`String`] as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sanitized<'a> {
+    name: &'a str,
+    max_len: usize,
+}
+
+impl Sanitized<'_> {
+    /// Sets the maximum length of the name in bytes, which is at least one.
+    ///
+    /// ```
+    /// # use pathbuf::sanitize;
+    /// #
+    /// assert_eq!(sanitize("überlang").max_len(4).to_string(), "übe");
+    /// ```
+    pub fn max_len(mut self, max: usize) -> Self {
+        self.max_len = max.max(1);
+        self
+    }
+
+    fn sanitized(&self) -> Cow<'_, str> {
+        let allowed = |c: char| {
+            !(c == '/'
+                || c == '\\'
+                || c.is_control()
+                || u8::try_from(c).is_ok_and(|byte| RESERVED_CHARS.contains(&byte)))
+        };
+
+        let mut name = if self.name.chars().all(allowed) {
+            Cow::Borrowed(self.name)
+        } else {
+            Cow::Owned(
+                self.name
+                    .chars()
+                    .map(|c| if allowed(c) { c } else { REPLACEMENT })
+                    .collect(),
+            )
+        };
+
+        truncate(&mut name, self.max_len);
+
+        if is_reserved_name(name.as_bytes()) {
+            name = Cow::Owned(format!("{REPLACEMENT}{name}"));
+            truncate(&mut name, self.max_len);
+        }
+
+        if name.is_empty() {
+            name = Cow::Owned(REPLACEMENT.to_string());
+        }
+
+        name
+    }
+}
+
+/// Truncates `name` to at most `max` bytes on a character boundary, then strips trailing dots and spaces.
+fn truncate(name: &mut Cow<'_, str>, max: usize) {
+    let mut end = name.len().min(max);
+
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let len = name[..end].trim_end_matches(['.', ' ']).len();
+
+    match name {
+        Cow::Borrowed(name) => *name = &name[..len],
+        Cow::Owned(name) => name.truncate(len),
+    }
+}
+
+impl<P: ?Sized> PathPart<P> for Sanitized<'_>
+where
+    str: AsRef<P>,
+{
+    fn push_to(&self, path: &mut PartWriter<'_, P>) {
+        path.push(&*self.sanitized());
+    }
+
+    fn len_hint(&self) -> usize {
+        self.name.len().min(self.max_len)
+    }
+}
+
+impl Display for Sanitized<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.sanitized())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize;
+    use crate::{pathbuf, try_pathbuf, PathPolicy};
+    use proptest::prelude::*;
+    use std::path::{Component, Path};
+
+    #[test]
+    fn replaces_characters() {
+        assert_eq!(sanitize("a/b\\c").to_string(), "a_b_c");
+        assert_eq!(sanitize("a\0b\tc\u{7f}d\u{85}").to_string(), "a_b_c_d_");
+        assert_eq!(sanitize("<a>:\"b\"|c?*").to_string(), "_a___b__c__");
+        assert_eq!(sanitize("Grüße, Welt!").to_string(), "Grüße, Welt!");
+    }
+
+    #[test]
+    fn strips_trailing_dots_and_spaces() {
+        assert_eq!(sanitize("notes. . ").to_string(), "notes");
+        assert_eq!(sanitize(" .hidden").to_string(), " .hidden");
+        assert_eq!(sanitize(".").to_string(), "_");
+        assert_eq!(sanitize("..").to_string(), "_");
+        assert_eq!(sanitize("").to_string(), "_");
+    }
+
+    #[test]
+    fn reserved_names() {
+        assert_eq!(sanitize("CON").to_string(), "_CON");
+        assert_eq!(sanitize("com1.txt").to_string(), "_com1.txt");
+        assert_eq!(sanitize("console").to_string(), "console");
+        assert_eq!(sanitize("console").max_len(3).to_string(), "_co");
+        assert_eq!(sanitize("nul.").max_len(4).to_string(), "_nul");
+    }
+
+    #[test]
+    fn truncates_on_char_boundaries() {
+        assert_eq!(sanitize("ab€").max_len(4).to_string(), "ab");
+        assert_eq!(sanitize("ab€").max_len(5).to_string(), "ab€");
+        assert_eq!(sanitize("a. b").max_len(3).to_string(), "a");
+        assert_eq!(sanitize("€").max_len(0).to_string(), "_");
+        assert_eq!(sanitize(&"x".repeat(300)).to_string().len(), 255);
+    }
+
+    #[test]
+    fn passes_portable_policy() {
+        let title = "../../etc/passwd";
+
+        assert_eq!(
+            try_pathbuf![policy = PathPolicy::portable(); "uploads", sanitize(title)],
+            Ok(pathbuf!["uploads", ".._.._etc_passwd"])
+        );
+    }
+
+    /// Names which are mostly made of the characters `sanitize` cares about, or anything at all.
+    fn name() -> impl Strategy<Value = String> {
+        prop_oneof!["[./\\\\ \t\0:?cCoOnNuUlLmM1€ü]{0,12}", any::<String>()]
+    }
+
+    proptest! {
+        #[test]
+        fn single_normal_component(name in name(), max in 0..32_usize) {
+            let sanitized = sanitize(&name).max_len(max).to_string();
+            let path = pathbuf!["base", sanitize(&name).max_len(max)];
+
+            prop_assert!(sanitized.len() <= max.max(1));
+            prop_assert!(matches!(
+                Path::new(&sanitized).components().collect::<Vec<_>>()[..],
+                [Component::Normal(_)]
+            ));
+            prop_assert_eq!(path, Path::new("base").join(&sanitized));
+            prop_assert_eq!(
+                try_pathbuf![policy = PathPolicy::portable(); "base", sanitize(&name).max_len(max)],
+                Ok(Path::new("base").join(&sanitized))
+            );
+        }
+    }
+}
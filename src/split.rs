@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::part::{PartWriter, PathPart};
+
+/// Splits a `/`-separated string into components, like `split("assets/img/logo.png")`.
+///
+/// A string literal with a separator in it is pushed as a single component, so the `/` ends up next to the native
+/// separators of Windows. `split` pushes every segment on its own instead, so the path only has native separators on
+/// every platform:
+///
+/// ```
+/// # use pathbuf::{pathbuf, split};
+/// # use std::path::PathBuf;
+/// #
+/// let theme = "dark";
+///
+/// assert_eq!(
+///     pathbuf!["static", theme, split("img/icons/logo.png")],
+///     ["static", "dark", "img", "icons", "logo.png"].iter().collect::<PathBuf>(),
+/// );
+/// ```
+///
+/// Only `/` separates segments, and empty segments are skipped, so a leading `/` does not replace the path before
+/// it. Any other segment is pushed as is, following the rules of the macro it is passed to, which means that
+/// [`try_pathbuf!`][crate::try_pathbuf] still rejects a `..` segment, even in the first argument.
+pub fn split(path: &str) -> Split<'_> {
+    Split { path }
+}
+
+/// A `/`-separated string pushed as separate components, returned by [`split`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Split<'a> {
+    path: &'a str,
+}
+
+impl<P: ?Sized> PathPart<P> for Split<'_>
+where
+    str: AsRef<P>,
+{
+    fn push_to(&self, path: &mut PartWriter<'_, P>) {
+        for segment in self.path.split('/').filter(|segment| !segment.is_empty()) {
+            path.push(segment);
+        }
+    }
+
+    fn len_hint(&self) -> usize {
+        self.path.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split;
+    use crate::{pathbuf, try_pathbuf, PathBufError};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn segments() {
+        let p = pathbuf!["static", split("img/icons/logo.png")];
+        assert_eq!(
+            p,
+            ["static", "img", "icons", "logo.png"]
+                .iter()
+                .collect::<PathBuf>()
+        );
+        assert_eq!(p.capacity(), p.as_os_str().len());
+    }
+
+    #[test]
+    fn empty_segments() {
+        assert_eq!(
+            pathbuf!["static", split("/img//logo.png/")],
+            pathbuf!["static", "img", "logo.png"]
+        );
+        assert_eq!(pathbuf!["static", split("")], Path::new("static"));
+    }
+
+    #[test]
+    fn checked() {
+        assert_eq!(
+            try_pathbuf!["static", split("img/logo.png")],
+            Ok(pathbuf!["static", "img", "logo.png"])
+        );
+        assert_eq!(
+            try_pathbuf!["static", split("img/../../etc")],
+            Err(PathBufError::ParentDir { index: 1 })
+        );
+        assert_eq!(
+            try_pathbuf![split("a/../../etc")],
+            Err(PathBufError::ParentDir { index: 0 })
+        );
+    }
+
+    #[cfg(feature = "camino")]
+    #[test]
+    fn utf8() {
+        use crate::utf8_pathbuf;
+
+        assert_eq!(
+            utf8_pathbuf!["static", split("img/logo.png")],
+            utf8_pathbuf!["static", "img", "logo.png"]
+        );
+    }
+}
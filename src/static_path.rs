@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ffi::OsStr;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A path joined at compile time by [`path!`][crate::path].
+///
+/// As [`Path::new`] can't be called in constants yet, the path is kept as a `&'static str` and dereferences to a
+/// [`Path`]. Use [`StaticPath::as_path`] to get a `&'static Path`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StaticPath(&'static str);
+
+impl StaticPath {
+    #[doc(hidden)]
+    pub const fn __new(path: &'static str) -> StaticPath {
+        StaticPath(path)
+    }
+
+    /// Returns the path.
+    pub fn as_path(self) -> &'static Path {
+        Path::new(self.0)
+    }
+
+    /// Returns the path as a string slice.
+    pub const fn as_str(self) -> &'static str {
+        self.0
+    }
+
+    /// Copies the path into a [`PathBuf`].
+    pub fn to_path_buf(self) -> PathBuf {
+        PathBuf::from(self.0)
+    }
+}
+
+impl Debug for StaticPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_path(), f)
+    }
+}
+
+impl Deref for StaticPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<Path> for StaticPath {
+    fn as_ref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<OsStr> for StaticPath {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::new(self.0)
+    }
+}
+
+impl From<StaticPath> for &'static Path {
+    fn from(path: StaticPath) -> &'static Path {
+        path.as_path()
+    }
+}
+
+impl From<StaticPath> for PathBuf {
+    fn from(path: StaticPath) -> PathBuf {
+        path.to_path_buf()
+    }
+}
+
+/// Fails the compilation of [`path!`][crate::path] for components which would not be joined like by
+/// [`PathBuf::push`].
+pub const fn check_literals(parts: &[&str]) {
+    let mut index = 0;
+
+    while index < parts.len() {
+        check_literal(
+            parts[index].as_bytes(),
+            index == 0,
+            index + 1 == parts.len(),
+        );
+        index += 1;
+    }
+}
+
+const fn check_literal(bytes: &[u8], first: bool, last: bool) {
+    // `PathBuf::push` adds no separator after one, so the joined path would have two.
+    if !last {
+        if let [.., byte] = bytes {
+            assert!(
+                !is_separator(*byte),
+                "a component of `path!` before another must not end with a separator"
+            );
+        }
+    }
+
+    if first {
+        assert!(
+            !bytes.is_empty(),
+            "the first component of `path!` must not be empty"
+        );
+        return;
+    }
+
+    // `PathBuf::push` adds only a separator for an empty component, which the next one would be joined to.
+    assert!(
+        !bytes.is_empty(),
+        "a component of `path!` after the first must not be empty"
+    );
+
+    if let [byte, ..] = bytes {
+        assert!(
+            !is_separator(*byte),
+            "a component of `path!` after the first must not start with a separator"
+        );
+    }
+
+    if cfg!(windows) {
+        if let [letter, b':', ..] = bytes {
+            assert!(
+                !letter.is_ascii_alphabetic(),
+                "a component of `path!` after the first must not have a prefix"
+            );
+        }
+    }
+}
+
+const fn is_separator(byte: u8) -> bool {
+    byte == b'/' || (cfg!(windows) && byte == b'\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_literals;
+
+    #[test]
+    fn accepts_separators_pushed_alike() {
+        check_literals(&["/"]);
+        check_literals(&["/etc", "app/"]);
+        check_literals(&["etc", "app/config"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "before another must not end with a separator")]
+    fn trailing_separator() {
+        check_literals(&["etc/", "app"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "before another must not end with a separator")]
+    fn root_before_another() {
+        check_literals(&["/", "etc"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "after the first must not be empty")]
+    fn empty_component() {
+        check_literals(&["etc", "", "app"]);
+    }
+}
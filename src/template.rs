@@ -0,0 +1,489 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::checked::check_component;
+use crate::PathBufError;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt::{self, Display, Formatter, Write};
+use std::hash::{BuildHasher, Hash};
+use std::path::{is_separator, Path, PathBuf};
+use std::str::FromStr;
+
+/// A path layout like `{root}/{tenant}/{yyyy}/{mm}/{id}.json`, parsed once and rendered many times.
+///
+/// Components are separated by `/`, and placeholders are written like in [`format!`][std_format], with `{{` and `}}`
+/// for literal braces. A placeholder name consists of ASCII letters, digits and `_`.
+///
+/// Parsing rejects `..` components. Rendering follows the rules of [`try_pathbuf!`][crate::try_pathbuf]: only the
+/// first component may be absolute, and no substituted value may replace or climb out of the path before it.
+///
+/// ```
+/// # use pathbuf::PathTemplate;
+/// # use std::collections::HashMap;
+/// # use std::path::PathBuf;
+/// #
+/// let template: PathTemplate = "{root}/{tenant}/{id}.json".parse()?;
+///
+/// let args = HashMap::from([("root", "/srv"), ("tenant", "acme"), ("id", "42")]);
+/// assert_eq!(template.render(&args)?, PathBuf::from("/srv/acme/42.json"));
+///
+/// let args = HashMap::from([("root", "/srv"), ("tenant", "../etc"), ("id", "42")]);
+/// assert!(template.render(&args).is_err());
+/// # Ok::<(), pathbuf::TemplateError>(())
+/// ```
+///
+/// [std_format]: https://doc.rust-lang.org/std/macro.format.html "Documentation for std::format (macro)"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathTemplate {
+    source: String,
+    components: Vec<Vec<Segment>>,
+    literal_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(String),
+}
+
+/// The bytes reserved for each placeholder when rendering, as their values are only known once written.
+const PLACEHOLDER_LEN: usize = 16;
+
+impl PathTemplate {
+    /// Parses and validates a template.
+    pub fn new(template: &str) -> Result<Self, TemplateError> {
+        let mut components = Vec::new();
+        let mut component = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|&(_, c)| c == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|&(_, c)| c == '}').is_some() => literal.push('}'),
+                '}' => return Err(TemplateError::UnmatchedBrace { offset }),
+                '{' => {
+                    let mut name = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) if !name.is_empty() => break,
+                            Some((_, c)) if c.is_ascii_alphanumeric() || c == '_' => name.push(c),
+                            Some(_) => return Err(TemplateError::InvalidName { offset }),
+                            None => return Err(TemplateError::UnclosedPlaceholder { offset }),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        component.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    component.push(Segment::Placeholder(name));
+                }
+                // A leading separator is kept, so that the first component is absolute.
+                c if is_separator(c) && offset == 0 => literal.push(c),
+                c if is_separator(c) => {
+                    if !literal.is_empty() {
+                        component.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    // Repeated and trailing separators don't start a new component.
+                    if !component.is_empty() {
+                        components.push(std::mem::take(&mut component));
+                    }
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            component.push(Segment::Literal(literal));
+        }
+
+        if !component.is_empty() {
+            components.push(component);
+        }
+
+        if components.is_empty() {
+            return Err(TemplateError::Empty);
+        }
+
+        let mut literal_len = 0;
+
+        for (index, component) in components.iter().enumerate() {
+            if let [Segment::Literal(literal)] = component.as_slice() {
+                if literal == ".." {
+                    return Err(TemplateError::ParentDir { component: index });
+                }
+            }
+
+            for segment in component {
+                literal_len += match segment {
+                    Segment::Literal(literal) => literal.len(),
+                    Segment::Placeholder(_) => PLACEHOLDER_LEN,
+                };
+            }
+        }
+
+        Ok(PathTemplate {
+            source: template.to_owned(),
+            components,
+            literal_len,
+        })
+    }
+
+    /// Returns the template as it was parsed.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the names of the placeholders, in order of appearance and possibly repeated.
+    pub fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.components
+            .iter()
+            .flatten()
+            .filter_map(|segment| match segment {
+                Segment::Placeholder(name) => Some(name.as_str()),
+                Segment::Literal(_) => None,
+            })
+    }
+
+    /// Renders the template, substituting the placeholders with `args`.
+    ///
+    /// Returns an error if a placeholder has no value, or if a component after the first one is absolute or
+    /// contains a `..` segment once its placeholders are substituted. The index of the [`PathBufError`] is that of
+    /// the component.
+    pub fn render(&self, args: &(impl TemplateArgs + ?Sized)) -> Result<PathBuf, TemplateError> {
+        let mut path = PathBuf::with_capacity(self.literal_len + self.components.len());
+        let mut rendered = OsString::new();
+
+        for (index, component) in self.components.iter().enumerate() {
+            rendered.clear();
+
+            for segment in component {
+                match segment {
+                    Segment::Literal(literal) => rendered.push(literal),
+                    Segment::Placeholder(name) => {
+                        let mut writer = ArgWriter { buf: &mut rendered };
+
+                        if !args.write_arg(name, &mut writer) {
+                            return Err(TemplateError::Missing { name: name.clone() });
+                        }
+                    }
+                }
+            }
+
+            if index > 0 {
+                check_component(index, Path::new(&rendered)).map_err(|error| {
+                    TemplateError::Rejected {
+                        name: first_placeholder(component).to_owned(),
+                        error,
+                    }
+                })?;
+            }
+
+            path.push(&rendered);
+        }
+
+        Ok(path)
+    }
+}
+
+/// Returns the name of the first placeholder of a component which was rejected once rendered.
+///
+/// Literal components are validated while parsing, so a rejected component always has one.
+fn first_placeholder(component: &[Segment]) -> &str {
+    component
+        .iter()
+        .find_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name.as_str()),
+            Segment::Literal(_) => None,
+        })
+        .unwrap_or_default()
+}
+
+impl FromStr for PathTemplate {
+    type Err = TemplateError;
+
+    fn from_str(template: &str) -> Result<Self, TemplateError> {
+        PathTemplate::new(template)
+    }
+}
+
+impl Display for PathTemplate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// The values for the placeholders of a [`PathTemplate`].
+///
+/// It is implemented for maps from names to anything implementing [`AsRef<Path>`][std_path_path], and can be
+/// implemented for a struct to render it without building a map:
+///
+/// ```
+/// # use pathbuf::{ArgWriter, PathTemplate, TemplateArgs};
+/// # use std::path::PathBuf;
+/// #
+/// struct Document {
+///     tenant: String,
+///     year: u16,
+///     id: u64,
+/// }
+///
+/// impl TemplateArgs for Document {
+///     fn write_arg(&self, name: &str, out: &mut ArgWriter<'_>) -> bool {
+///         match name {
+///             "tenant" => out.push(&self.tenant),
+///             "yyyy" => out.push_fmt(format_args!("{:04}", self.year)),
+///             "id" => out.push_fmt(format_args!("{}", self.id)),
+///             _ => return false,
+///         }
+///
+///         true
+///     }
+/// }
+///
+/// let template = PathTemplate::new("docs/{tenant}/{yyyy}/{id}.json")?;
+/// let document = Document { tenant: "acme".into(), year: 2024, id: 42 };
+///
+/// assert_eq!(template.render(&document)?, PathBuf::from("docs/acme/2024/42.json"));
+/// # Ok::<(), pathbuf::TemplateError>(())
+/// ```
+///
+/// [std_path_path]: https://doc.rust-lang.org/std/path/struct.Path.html "Documentation for std::path::Path (struct)"
+pub trait TemplateArgs {
+    /// Writes the value of the placeholder `name` and returns `true`, or returns `false` if it has none.
+    fn write_arg(&self, name: &str, out: &mut ArgWriter<'_>) -> bool;
+}
+
+impl<T: TemplateArgs + ?Sized> TemplateArgs for &T {
+    fn write_arg(&self, name: &str, out: &mut ArgWriter<'_>) -> bool {
+        (**self).write_arg(name, out)
+    }
+}
+
+impl<K, V, S> TemplateArgs for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<Path>,
+    S: BuildHasher,
+{
+    fn write_arg(&self, name: &str, out: &mut ArgWriter<'_>) -> bool {
+        self.get(name).map(|value| out.push(value)).is_some()
+    }
+}
+
+impl<K: Borrow<str> + Ord, V: AsRef<Path>> TemplateArgs for BTreeMap<K, V> {
+    fn write_arg(&self, name: &str, out: &mut ArgWriter<'_>) -> bool {
+        self.get(name).map(|value| out.push(value)).is_some()
+    }
+}
+
+/// Receives the value of a placeholder from [`TemplateArgs`].
+pub struct ArgWriter<'a> {
+    buf: &'a mut OsString,
+}
+
+impl ArgWriter<'_> {
+    /// Appends a value.
+    pub fn push<P: AsRef<Path> + ?Sized>(&mut self, value: &P) {
+        self.buf.push(value.as_ref());
+    }
+
+    /// Appends a formatted value, without an intermediate [`String`].
+    pub fn push_fmt(&mut self, args: fmt::Arguments<'_>) {
+        self.buf
+            .write_fmt(args)
+            .expect("writing to an OsString cannot fail");
+    }
+}
+
+/// The error returned when a [`PathTemplate`] cannot be parsed or rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TemplateError {
+    /// The template has no components.
+    Empty,
+    /// A `{` at the given byte offset is not closed.
+    UnclosedPlaceholder { offset: usize },
+    /// A `}` at the given byte offset is not part of a placeholder and not escaped as `}}`.
+    UnmatchedBrace { offset: usize },
+    /// The placeholder starting at the given byte offset has an empty or invalid name.
+    InvalidName { offset: usize },
+    /// The component with the given zero-based index is a `..` literal.
+    ParentDir { component: usize },
+    /// There is no value for a placeholder.
+    Missing { name: String },
+    /// The component containing the placeholder was rejected once rendered.
+    Rejected { name: String, error: PathBufError },
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Empty => write!(f, "the template is empty"),
+            TemplateError::UnclosedPlaceholder { offset } => {
+                write!(f, "unclosed `{{` at byte {offset}")
+            }
+            TemplateError::UnmatchedBrace { offset } => {
+                write!(f, "unmatched `}}` at byte {offset}")
+            }
+            TemplateError::InvalidName { offset } => {
+                write!(f, "invalid placeholder name at byte {offset}")
+            }
+            TemplateError::ParentDir { component } => {
+                write!(f, "component {component} is a `..` literal")
+            }
+            TemplateError::Missing { name } => write!(f, "no value for placeholder `{name}`"),
+            TemplateError::Rejected { name, error } => {
+                write!(f, "value of placeholder `{name}` is rejected: {error}")
+            }
+        }
+    }
+}
+
+impl Error for TemplateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TemplateError::Rejected { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgWriter, PathTemplate, TemplateArgs, TemplateError};
+    use crate::PathBufError;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+
+    #[test]
+    fn render_map() {
+        let template = PathTemplate::new("{root}/{tenant}/{yyyy}/{mm}/{id}.json").unwrap();
+        let args = HashMap::from([
+            ("root", "/srv/data"),
+            ("tenant", "acme"),
+            ("yyyy", "2024"),
+            ("mm", "03"),
+            ("id", "42"),
+        ]);
+
+        assert_eq!(
+            template.render(&args).unwrap(),
+            PathBuf::from("/srv/data/acme/2024/03/42.json")
+        );
+    }
+
+    #[test]
+    fn render_struct() {
+        struct Args {
+            id: u32,
+        }
+
+        impl TemplateArgs for Args {
+            fn write_arg(&self, name: &str, out: &mut ArgWriter<'_>) -> bool {
+                match name {
+                    "id" => out.push_fmt(format_args!("{:06}", self.id)),
+                    _ => return false,
+                }
+
+                true
+            }
+        }
+
+        let template = PathTemplate::new("segments/segment-{id}.bin").unwrap();
+        assert_eq!(
+            template.render(&Args { id: 17 }).unwrap(),
+            PathBuf::from("segments/segment-000017.bin")
+        );
+    }
+
+    #[test]
+    fn parse_structure() {
+        let template = PathTemplate::new("/srv//{a}-{{{b}}}/").unwrap();
+        assert_eq!(template.placeholders().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(template.to_string(), "/srv//{a}-{{{b}}}/");
+
+        let args = BTreeMap::from([("a", "x"), ("b", "y")]);
+        assert_eq!(template.render(&args).unwrap(), PathBuf::from("/srv/x-{y}"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(PathTemplate::new(""), Err(TemplateError::Empty));
+        assert_eq!(
+            PathTemplate::new("a/{id"),
+            Err(TemplateError::UnclosedPlaceholder { offset: 2 })
+        );
+        assert_eq!(
+            PathTemplate::new("a}"),
+            Err(TemplateError::UnmatchedBrace { offset: 1 })
+        );
+        assert_eq!(
+            PathTemplate::new("a/{}"),
+            Err(TemplateError::InvalidName { offset: 2 })
+        );
+        assert_eq!(
+            PathTemplate::new("{a b}"),
+            Err(TemplateError::InvalidName { offset: 0 })
+        );
+        assert_eq!(
+            PathTemplate::new("{root}/../{id}"),
+            Err(TemplateError::ParentDir { component: 1 })
+        );
+    }
+
+    #[test]
+    fn missing_value() {
+        let template = PathTemplate::new("{root}/{id}").unwrap();
+        let args = HashMap::from([("root", "data")]);
+
+        assert_eq!(
+            template.render(&args),
+            Err(TemplateError::Missing {
+                name: String::from("id")
+            })
+        );
+    }
+
+    #[test]
+    fn rejected_values() {
+        let template = PathTemplate::new("{root}/{tenant}/{id}.json").unwrap();
+
+        let args = HashMap::from([("root", "/srv"), ("tenant", "/etc"), ("id", "42")]);
+        assert_eq!(
+            template.render(&args),
+            Err(TemplateError::Rejected {
+                name: String::from("tenant"),
+                error: PathBufError::Absolute { index: 1 }
+            })
+        );
+
+        let args = HashMap::from([("root", "/srv"), ("tenant", "acme/.."), ("id", "42")]);
+        assert_eq!(
+            template.render(&args),
+            Err(TemplateError::Rejected {
+                name: String::from("tenant"),
+                error: PathBufError::ParentDir { index: 1 }
+            })
+        );
+    }
+
+    #[test]
+    fn rejected_after_substitution() {
+        let template = PathTemplate::new("data/{a}.").unwrap();
+        let args = HashMap::from([("a", ".")]);
+
+        assert_eq!(
+            template.render(&args),
+            Err(TemplateError::Rejected {
+                name: String::from("a"),
+                error: PathBufError::ParentDir { index: 1 }
+            })
+        );
+    }
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::{additional, Builder, PartFromStr};
+use crate::part::{PartWriter, PathPart};
+use typed_path::{Encoding, Path, PathBuf};
+
+impl<T: Encoding> Builder for PathBuf<T> {
+    type Part = Path<T>;
+
+    fn reserve(&mut self, joined_len: usize) {
+        let _ = self.try_reserve_exact(additional(self.as_bytes().is_empty(), joined_len));
+    }
+
+    fn push_part(&mut self, part: &Path<T>) {
+        self.push(part);
+    }
+
+    type Extension = [u8];
+
+    fn extension_len(extension: &[u8]) -> usize {
+        extension.len()
+    }
+
+    fn set_extension(&mut self, extension: &[u8]) {
+        PathBuf::set_extension(self, extension);
+    }
+}
+
+impl<T: Encoding> PartFromStr for Path<T> {
+    fn part_from_str(part: &str) -> &Path<T> {
+        Path::new(part)
+    }
+}
+
+impl<E: Encoding, T: AsRef<Path<E>> + ?Sized> PathPart<Path<E>> for T {
+    fn push_to(&self, path: &mut PartWriter<'_, Path<E>>) {
+        path.push(self.as_ref());
+    }
+
+    fn len_hint(&self) -> usize {
+        self.as_ref().as_bytes().len()
+    }
+}
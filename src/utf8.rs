@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::build::{additional, Builder, PartFromStr};
+use crate::part::{PartWriter, PathPart};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::fmt;
+use std::mem;
+
+impl Builder for Utf8PathBuf {
+    type Part = Utf8Path;
+
+    fn reserve(&mut self, joined_len: usize) {
+        let _ = self.try_reserve_exact(additional(self.as_str().is_empty(), joined_len));
+    }
+
+    fn push_part(&mut self, part: &Utf8Path) {
+        self.push(part);
+    }
+
+    fn push_fmt(&mut self, args: fmt::Arguments<'_>) {
+        let mut path = mem::take(self).into_std_path_buf();
+        path.push_fmt(args);
+
+        *self = Utf8PathBuf::from_path_buf(path)
+            .expect("a path joined from UTF-8 components is valid UTF-8");
+    }
+
+    type Extension = str;
+
+    fn extension_len(extension: &str) -> usize {
+        extension.len()
+    }
+
+    fn set_extension(&mut self, extension: &str) {
+        Utf8PathBuf::set_extension(self, extension);
+    }
+}
+
+impl PartFromStr for Utf8Path {
+    fn part_from_str(part: &str) -> &Utf8Path {
+        Utf8Path::new(part)
+    }
+}
+
+impl<T: AsRef<Utf8Path> + ?Sized> PathPart<Utf8Path> for T {
+    fn push_to(&self, path: &mut PartWriter<'_, Utf8Path>) {
+        path.push(self.as_ref());
+    }
+
+    fn len_hint(&self) -> usize {
+        self.as_ref().as_str().len()
+    }
+}
@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::expand::Env;
+use std::path::{Path, PathBuf};
+
+/// A base directory of the [XDG Base Directory Specification][xdg].
+///
+/// This is what [`config_pathbuf!`][crate::config_pathbuf] and its siblings build on.
+///
+/// [xdg]: https://specifications.freedesktop.org/basedir-spec/latest/ "XDG Base Directory Specification"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseDir {
+    /// `$XDG_CONFIG_HOME`, defaulting to `~/.config`.
+    Config,
+    /// `$XDG_CACHE_HOME`, defaulting to `~/.cache`.
+    Cache,
+    /// `$XDG_DATA_HOME`, defaulting to `~/.local/share`.
+    Data,
+    /// `$XDG_STATE_HOME`, defaulting to `~/.local/state`.
+    State,
+    /// `$XDG_RUNTIME_DIR`, which has no default.
+    Runtime,
+}
+
+impl BaseDir {
+    /// Returns the name of the environment variable of this directory.
+    pub fn var(self) -> &'static str {
+        match self {
+            BaseDir::Config => "XDG_CONFIG_HOME",
+            BaseDir::Cache => "XDG_CACHE_HOME",
+            BaseDir::Data => "XDG_DATA_HOME",
+            BaseDir::State => "XDG_STATE_HOME",
+            BaseDir::Runtime => "XDG_RUNTIME_DIR",
+        }
+    }
+
+    /// Returns the default beneath the home directory, if this directory has one.
+    fn default_in_home(self) -> Option<&'static str> {
+        match self {
+            BaseDir::Config => Some(".config"),
+            BaseDir::Cache => Some(".cache"),
+            BaseDir::Data => Some(".local/share"),
+            BaseDir::State => Some(".local/state"),
+            BaseDir::Runtime => None,
+        }
+    }
+
+    /// Resolves the directory in `env`.
+    ///
+    /// Per the specification, a variable which is empty or holds a relative path is ignored, and the default
+    /// beneath the home directory is used instead. Returns `None` if there is neither, which is always the case for
+    /// [`BaseDir::Runtime`] without its variable, or if the home directory is relative as well.
+    pub fn resolve(self, env: &(impl Env + ?Sized)) -> Option<PathBuf> {
+        if let Some(dir) = env.var_os(self.var()).map(PathBuf::from) {
+            if dir.is_absolute() {
+                return Some(dir);
+            }
+        }
+
+        let home = env.home_dir().filter(|home| home.is_absolute())?;
+        let default = self.default_in_home()?;
+
+        Some(home.join(Path::new(default)))
+    }
+}
+
+/// Builds a path beneath an XDG base directory, evaluating the arguments only if the directory is resolved.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __xdg_pathbuf {
+    ( $dir:ident; env = $env:expr; $( $args:tt )* ) => {
+        match $crate::BaseDir::$dir.resolve(&$env) {
+            Some(base) => Some($crate::__pathbuf_build!(std::path::PathBuf = base; []; $($args)*)),
+            None => None,
+        }
+    };
+
+    ( $dir:ident; $( $args:tt )* ) => {
+        $crate::__xdg_pathbuf!($dir; env = $crate::ProcessEnv; $($args)*)
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] beneath the XDG config directory, like `~/.config/app/config.toml`.
+///
+/// The directory is `$XDG_CONFIG_HOME`, unless it is empty or relative, in which case the default `~/.config` is
+/// used, as the [specification][xdg] requires. The arguments are then pushed like with [`pathbuf!`][pathbuf].
+///
+/// Returns `None` if neither the variable nor the home directory is usable, without evaluating the arguments.
+///
+/// ```
+/// # use pathbuf::config_pathbuf;
+/// # use std::collections::HashMap;
+/// # use std::path::PathBuf;
+/// #
+/// # #[cfg(unix)]
+/// # {
+/// let env = HashMap::from([("HOME", "/home/alice"), ("XDG_CONFIG_HOME", "relative/config")]);
+///
+/// assert_eq!(
+///     config_pathbuf![env = env; "app", "config.toml"],
+///     Some(PathBuf::from("/home/alice/.config/app/config.toml")),
+/// );
+/// # }
+/// ```
+///
+/// Like with [`expand_pathbuf!`][expand_pathbuf], the variables are read from the process environment, unless
+/// another [`Env`][crate::Env] is given before the arguments.
+///
+/// [expand_pathbuf]: macro.expand_pathbuf.html
+/// [pathbuf]: macro.pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+/// [xdg]: https://specifications.freedesktop.org/basedir-spec/latest/ "XDG Base Directory Specification"
+#[macro_export]
+macro_rules! config_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__xdg_pathbuf!(Config; $($args)*)
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] beneath the XDG cache directory, like `~/.cache/app`.
+///
+/// The directory is `$XDG_CACHE_HOME`, defaulting to `~/.cache`. Otherwise this works like
+/// [`config_pathbuf!`][config_pathbuf].
+///
+/// ```
+/// # use pathbuf::cache_pathbuf;
+/// # use std::collections::HashMap;
+/// # use std::path::PathBuf;
+/// #
+/// # #[cfg(unix)]
+/// # {
+/// let env = HashMap::from([("XDG_CACHE_HOME", "/var/cache/alice")]);
+///
+/// assert_eq!(cache_pathbuf![env = env; "app"], Some(PathBuf::from("/var/cache/alice/app")));
+/// # }
+/// ```
+///
+/// [config_pathbuf]: macro.config_pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! cache_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__xdg_pathbuf!(Cache; $($args)*)
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] beneath the XDG data directory, like `~/.local/share/app`.
+///
+/// The directory is `$XDG_DATA_HOME`, defaulting to `~/.local/share`. Otherwise this works like
+/// [`config_pathbuf!`][config_pathbuf].
+///
+/// [config_pathbuf]: macro.config_pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! data_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__xdg_pathbuf!(Data; $($args)*)
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] beneath the XDG state directory, like `~/.local/state/app`.
+///
+/// The directory is `$XDG_STATE_HOME`, defaulting to `~/.local/state`. Otherwise this works like
+/// [`config_pathbuf!`][config_pathbuf].
+///
+/// [config_pathbuf]: macro.config_pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+#[macro_export]
+macro_rules! state_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__xdg_pathbuf!(State; $($args)*)
+    };
+}
+
+/// Creates a [`PathBuf`][std_path_pathbuf] beneath the XDG runtime directory, like `/run/user/1000/app.sock`.
+///
+/// The directory is `$XDG_RUNTIME_DIR`. As the [specification][xdg] gives it no default, this returns `None` if
+/// the variable is not set, empty or relative. Otherwise this works like [`config_pathbuf!`][config_pathbuf].
+///
+/// [config_pathbuf]: macro.config_pathbuf.html
+/// [std_path_pathbuf]: https://doc.rust-lang.org/std/path/struct.PathBuf.html "Documentation for std::path::PathBuf (struct)"
+/// [xdg]: https://specifications.freedesktop.org/basedir-spec/latest/ "XDG Base Directory Specification"
+#[macro_export]
+macro_rules! runtime_pathbuf {
+    ( $( $args:tt )* ) => {
+        $crate::__xdg_pathbuf!(Runtime; $($args)*)
+    };
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::BaseDir;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn defaults() {
+        let env = HashMap::from([("HOME", "/home/alice")]);
+
+        assert_eq!(
+            config_pathbuf![env = env; "app"],
+            Some(PathBuf::from("/home/alice/.config/app"))
+        );
+        assert_eq!(
+            cache_pathbuf![env = env; "app"],
+            Some(PathBuf::from("/home/alice/.cache/app"))
+        );
+        assert_eq!(
+            data_pathbuf![env = env; "app"],
+            Some(PathBuf::from("/home/alice/.local/share/app"))
+        );
+        assert_eq!(
+            state_pathbuf![env = env; "app"],
+            Some(PathBuf::from("/home/alice/.local/state/app"))
+        );
+        assert_eq!(runtime_pathbuf![env = env; "app.sock"], None);
+    }
+
+    #[test]
+    fn variables() {
+        let env = HashMap::from([
+            ("HOME", "/home/alice"),
+            ("XDG_DATA_HOME", "/data"),
+            ("XDG_RUNTIME_DIR", "/run/user/1000"),
+        ]);
+
+        let name = "app";
+        assert_eq!(
+            data_pathbuf![env = env; name, "db"; ext = "sqlite"],
+            Some(PathBuf::from("/data/app/db.sqlite"))
+        );
+        assert_eq!(
+            runtime_pathbuf![env = env; "{name}.sock"],
+            Some(PathBuf::from("/run/user/1000/app.sock"))
+        );
+    }
+
+    #[test]
+    fn ignores_empty_and_relative() {
+        let env = HashMap::from([
+            ("HOME", "/home/alice"),
+            ("XDG_CONFIG_HOME", ""),
+            ("XDG_CACHE_HOME", "cache"),
+            ("XDG_RUNTIME_DIR", "run"),
+        ]);
+
+        assert_eq!(
+            BaseDir::Config.resolve(&env),
+            Some(PathBuf::from("/home/alice/.config"))
+        );
+        assert_eq!(
+            BaseDir::Cache.resolve(&env),
+            Some(PathBuf::from("/home/alice/.cache"))
+        );
+        assert_eq!(BaseDir::Runtime.resolve(&env), None);
+    }
+
+    #[test]
+    fn unusable_home() {
+        let relative = HashMap::from([("HOME", "alice")]);
+        assert_eq!(BaseDir::Config.resolve(&relative), None);
+
+        let mut evaluated = false;
+        let none: HashMap<&str, &str> = HashMap::new();
+        let p = state_pathbuf![env = none; {
+            evaluated = true;
+            "app"
+        }];
+
+        assert_eq!(p, None);
+        assert!(!evaluated);
+    }
+
+    #[test]
+    fn absolute_variable() {
+        let env = HashMap::from([("XDG_CONFIG_HOME", "/etc/xdg")]);
+        let p = config_pathbuf![env = env; "app", "config.toml"].unwrap();
+        assert_eq!(p, PathBuf::from("/etc/xdg/app/config.toml"));
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks the diagnostics of `pathbuf!` with the `proc-macro` feature.
+
+#![cfg(feature = "proc-macro")]
+
+#[cfg(not(feature = "strict"))]
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
+
+#[cfg(feature = "strict")]
+#[test]
+fn strict() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/strict/*.rs");
+}
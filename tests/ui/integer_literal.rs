@@ -0,0 +1,5 @@
+use pathbuf::pathbuf;
+
+fn main() {
+    let _ = pathbuf!["data", 42, "file.txt"];
+}
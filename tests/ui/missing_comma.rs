@@ -0,0 +1,6 @@
+use pathbuf::pathbuf;
+
+fn main() {
+    let dir = "data";
+    let _ = pathbuf![dir "file.txt"];
+}
@@ -0,0 +1,8 @@
+#![deny(deprecated)]
+
+use pathbuf::pathbuf;
+
+fn main() {
+    let dir = "data";
+    let _ = pathbuf![dir, "../secrets", "key.pem"];
+}
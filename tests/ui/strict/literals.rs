@@ -0,0 +1,8 @@
+use pathbuf::pathbuf;
+
+fn main() {
+    let dir = "data";
+    let _ = pathbuf!["/var/lib", dir, "sub/file.txt"];
+    let _ = pathbuf![r"sub\file.txt"];
+    let _ = pathbuf![dir, "../secrets"];
+}
@@ -0,0 +1,7 @@
+use pathbuf::pathbuf;
+
+struct Config;
+
+fn main() {
+    let _ = pathbuf!["data", Config, "file.txt"];
+}